@@ -1,64 +1,214 @@
-use crate::{errors::{ResponseError, Code}, models::{Block, TransactionInfo, TransactionInfoConvertiable}};
+use crate::{config::{Commitment, Config}, errors::{ResponseError, Code}, models::{ApiVersion, Block, BlockRange, BlockReference, TransactionFilter, TransactionInfo, TransactionInfoConvertiable}, pool::RpcPool, queries::{BlockQuery, BlockRangeQuery}};
 
-use std::sync::Arc;
-use rocket::{State, serde::json::Json};
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcBlockConfig};
-use solana_sdk::commitment_config::CommitmentConfig;
-use solana_transaction_status::{UiTransactionEncoding, TransactionDetails};
+use std::{collections::HashSet, sync::Arc};
+use rocket::{State, futures::{StreamExt, stream}, serde::json::Json};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_config::RpcBlockConfig,
+    rpc_custom_error::{JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED},
+    rpc_request::RpcError
+};
+use solana_transaction_status::{UiConfirmedBlock, UiTransactionEncoding, TransactionDetails};
 
+fn block_config(commitment: Commitment, include_transactions: bool) -> RpcBlockConfig {
+    let transaction_details = if include_transactions {
+        TransactionDetails::Full
+    } else {
+        TransactionDetails::None
+    };
+    // Binary transactions are much smaller than the JSON ones, accounts loaded from lookup tables come with the meta
+    RpcBlockConfig {encoding: Some(UiTransactionEncoding::Base64), transaction_details: Some(transaction_details), rewards: Some(false), commitment: Some(commitment.to_commitment_config()), max_supported_transaction_version: Some(0)}
+}
+
+/// Skipped slots are reported with dedicated error codes, unlike blocks that are not available yet
+fn is_slot_skipped(err: &ClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if *code == JSON_RPC_SERVER_ERROR_SLOT_SKIPPED || *code == JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED
+    )
+}
 
-#[get("/blocks/latest")]
-pub fn get_latest_block(
-    rpc_client: &State<Arc<RpcClient>>
+fn slot_skipped_error(slot: u64) -> ResponseError {
+    ResponseError::SlotSkippedError(Json(Code { code: format!("Slot {} was skipped, no block was produced", slot) }))
+}
+
+fn to_block(slot: u64, block: UiConfirmedBlock, commitment: Commitment, include_transactions: bool, filter: &TransactionFilter) -> Result<Block, ResponseError> {
+    let reference = BlockReference {
+        slot,
+        block_height: block.block_height,
+        block_time: block.block_time,
+        hash: block.blockhash
+    };
+
+    let transactions: Vec<TransactionInfo> = match block.transactions {
+        Some(transactions_vec) if include_transactions => transactions_vec.iter()
+            .map(|transaction_meta| {
+                transaction_meta.to_transaction_info(&reference)
+            })
+            .filter(|transaction| transaction.as_ref().map_or(true, |transaction| filter.matches(transaction)))
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => Vec::new()
+    };
+
+    Ok(Block {
+        height: None,
+        hash: reference.hash,
+        transactions,
+        commitment,
+        slot,
+        block_height: reference.block_height,
+        parent_slot: block.parent_slot,
+        block_time: reference.block_time,
+        previous_blockhash: block.previous_blockhash
+    })
+}
+
+#[get("/blocks/latest?<query..>")]
+pub async fn get_latest_block(
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>,
+    query: BlockQuery,
+    version: ApiVersion
 ) -> Result<Json<Block>, ResponseError> {
+    let commitment = query.commitment.unwrap_or(config.commitment).for_blocks();
 
-    let slot = rpc_client.get_slot()
+    // The slot has to be resolved with the same commitment as the block, otherwise it may not be available yet
+    let slot = rpc_pool.call(|rpc_client| rpc_client.get_slot_with_commitment(commitment.to_commitment_config())).await
     .map_err(|err| {
-        log::error!("Error getting latest slot: {}", err); 
+        log::error!("Error getting latest slot: {}", err);
         ResponseError::LatestSlotError(Json(Code { code: "Failed during getting the latest slot".to_string()}))
     })?;
 
-    rpc_client.get_block_with_config(slot, RpcBlockConfig {encoding: Some(UiTransactionEncoding::Json), transaction_details: Some(TransactionDetails::Full), rewards: Some(false), commitment: Some(CommitmentConfig::finalized()), max_supported_transaction_version: Some(0)})
-    .map(|block| Json(Block{
-        height: slot,
-        hash: block.blockhash,
-        transactions: vec![]}))
+    let block = rpc_pool.call(|rpc_client| rpc_client.get_block_with_config(slot, block_config(commitment, query.include_transactions))).await
     .map_err(|err| {
         log::error!("Error getting latest block: {}", err);
-        ResponseError::GetBlockError(Json(Code { code: "Failed during getting the block with given slot".to_string()}))})
+        ResponseError::GetBlockError(Json(Code { code: "Failed during getting the block with given slot".to_string()}))})?;
+
+    Ok(Json(to_block(slot, block, commitment, query.include_transactions, &TransactionFilter::default())?.versioned(version)))
 }
 
-#[get("/blocks/<slot>")]
-pub fn scan_block_transactions_from_slot(
-    rpc_client: &State<Arc<RpcClient>>,
-    slot: u64
+#[get("/blocks/<slot>?<commitment>&<addresses>&<contracts>")]
+pub async fn scan_block_transactions_from_slot(
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>,
+    slot: u64,
+    commitment: Option<Commitment>,
+    addresses: Option<&str>,
+    contracts: Option<&str>,
+    version: ApiVersion
 ) -> Result<Json<Block>, ResponseError> {
+    let commitment = commitment.unwrap_or(config.commitment).for_blocks();
+    let filter = TransactionFilter::new(addresses, contracts)?;
 
-    let block = rpc_client.get_block_with_config(slot, RpcBlockConfig {encoding: Some(UiTransactionEncoding::Json), transaction_details: Some(TransactionDetails::Full), rewards: Some(false), commitment: Some(CommitmentConfig::finalized()), max_supported_transaction_version: Some(0)})
+    let block = rpc_pool.call(|rpc_client| rpc_client.get_block_with_config(slot, block_config(commitment, true))).await
         .map_err(|err| {
+            if is_slot_skipped(&err) {
+                return slot_skipped_error(slot);
+            }
             log::error!("Failed during getting the block with given slot: {}", err);
-            ResponseError::GetBlockError(Json(Code { code: "Failed during getting the block with given slot".to_string() })) 
+            ResponseError::GetBlockError(Json(Code { code: "Failed during getting the block with given slot".to_string() }))
         })?;
-    let hash = block.blockhash;
 
-    let transactions_vec = match block.transactions {
-        Some(transactions_vec) => {
-            transactions_vec
+    if block.transactions.is_none() {
+        return Err(ResponseError::BlockTransactionsError(Json(Code { code: "Failed during getting transactions from given block".to_string() })));
+    }
+
+    Ok(Json(to_block(slot, block, commitment, true, &filter)?.versioned(version)))
+}
+
+#[get("/blocks?<query..>")]
+pub async fn scan_block_range(
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>,
+    query: BlockRangeQuery,
+    version: ApiVersion
+) -> Result<Json<BlockRange>, ResponseError> {
+    let commitment = query.commitment.unwrap_or(config.commitment).for_blocks();
+    let filter = TransactionFilter::new(query.addresses.as_deref(), query.contracts.as_deref())?;
+    let from = query.from;
+
+    let (to, produced_slots) = match (query.to, query.limit) {
+        (Some(to), None) => {
+            if to < from || to - from >= config.max_block_range {
+                return Err(ResponseError::InvalidBlockRangeError(Json(Code { code: format!("Range has to contain between 1 and {} slots", config.max_block_range) })));
+            }
+
+            // Slots after the latest one are not produced yet, they must not be reported as skipped
+            let latest_slot = rpc_pool.call(|rpc_client| rpc_client.get_slot_with_commitment(commitment.to_commitment_config())).await
+                .map_err(|err| {
+                    log::error!("Error getting latest slot: {}", err);
+                    ResponseError::LatestSlotError(Json(Code { code: "Failed during getting the latest slot".to_string()}))
+                })?;
+            if from > latest_slot {
+                return Err(ResponseError::InvalidBlockRangeError(Json(Code { code: format!("Range starts after the latest slot {}", latest_slot) })));
+            }
+            let to = to.min(latest_slot);
+
+            let produced_slots = rpc_pool.call(|rpc_client| rpc_client.get_blocks_with_commitment(from, Some(to), commitment.to_commitment_config())).await
+                .map_err(|err| {
+                    log::error!("Failed during getting the blocks of the range: {}", err);
+                    ResponseError::GetBlocksError(Json(Code { code: "Failed during getting the blocks of the range".to_string() }))
+                })?;
+            (to, produced_slots)
         },
-        None => {
-            return Err(ResponseError::BlockTransactionsError(Json(Code { code: "Failed during getting transactions from given block".to_string() })));
+        (None, Some(limit)) => {
+            if limit == 0 || limit > config.max_block_range {
+                return Err(ResponseError::InvalidBlockRangeError(Json(Code { code: format!("Limit has to be between 1 and {}", config.max_block_range) })));
+            }
+
+            let produced_slots = rpc_pool.call(|rpc_client| rpc_client.get_blocks_with_limit_and_commitment(from, limit as usize, commitment.to_commitment_config())).await
+                .map_err(|err| {
+                    log::error!("Failed during getting the blocks of the range: {}", err);
+                    ResponseError::GetBlocksError(Json(Code { code: "Failed during getting the blocks of the range".to_string() }))
+                })?;
+            // Without any produced block there is no known end of the range
+            let to = produced_slots.last().copied().unwrap_or(from);
+            (to, produced_slots)
+        },
+        _ => {
+            return Err(ResponseError::InvalidBlockRangeError(Json(Code { code: "Exactly one of to and limit has to be given".to_string() })));
         }
     };
 
-    let transactions: Vec<TransactionInfo> = transactions_vec.iter()
-        .map(|transaction_meta| {
-            transaction_meta.to_transaction_info(slot, &hash)
-        }).collect::<Result<Vec<_>, _>>()?;
+    let rpc_pool = rpc_pool.inner();
+    let fetched_blocks: Vec<(u64, Result<UiConfirmedBlock, ClientError>)> = stream::iter(produced_slots.iter().copied())
+        .map(|slot| async move {
+            (slot, rpc_pool.call(|rpc_client| rpc_client.get_block_with_config(slot, block_config(commitment, true))).await)
+        })
+        .buffered(config.block_scan_concurrency)
+        .collect()
+        .await;
+
+    let mut blocks: Vec<Block> = Vec::with_capacity(fetched_blocks.len());
+    let mut skipped_during_scan: HashSet<u64> = HashSet::new();
+    for (slot, block) in fetched_blocks {
+        match block {
+            Ok(block) => blocks.push(to_block(slot, block, commitment, true, &filter)?.versioned(version)),
+            Err(err) if is_slot_skipped(&err) => {
+                skipped_during_scan.insert(slot);
+            },
+            Err(err) => {
+                log::error!("Failed during getting the block with given slot: {}", err);
+                return Err(ResponseError::GetBlockError(Json(Code { code: format!("Failed during getting the block with slot {}", slot) })));
+            }
+        }
+    }
+
+    let produced_slots: HashSet<u64> = produced_slots.into_iter().collect();
+    let skipped_slots: Vec<u64> = if produced_slots.is_empty() && query.to.is_none() {
+        Vec::new()
+    } else {
+        (from..=to)
+            .filter(|slot| !produced_slots.contains(slot) || skipped_during_scan.contains(slot))
+            .collect()
+    };
 
-    Ok(Json(Block {
-        /// We use slot instead of height in Solana
-        height: slot, 
-        hash,
-        transactions
+    Ok(Json(BlockRange {
+        from,
+        to,
+        blocks,
+        skipped_slots,
+        commitment
     }))
 }
@@ -1 +1,244 @@
-pub const SOL_PRECISION: u32 = 9;
\ No newline at end of file
+use crate::errors::{ResponseError, Code};
+use rocket::{figment::{Figment, providers::{Env, Format, Serialized, Toml}}, serde::json::Json};
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_transaction_status::TransactionConfirmationStatus;
+use std::time::Duration;
+
+/// Decimals of SOL, amounts of SOL are kept in lamports
+pub const SOL_PRECISION: u8 = 9;
+
+/// Environment variable holding the path of the TOML configuration file
+pub const CONFIG_PATH_ENV: &str = "SOLANA_CONFIG";
+pub const DEFAULT_CONFIG_PATH: &str = "Solana.toml";
+/// Prefix of the environment variables overriding the TOML file (e.g. SOLANA_CLUSTER)
+pub const CONFIG_ENV_PREFIX: &str = "SOLANA_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+    /// Requires `rpc_url` to be set
+    Custom
+}
+
+impl Cluster {
+    pub fn default_url(&self) -> Option<&'static str> {
+        match self {
+            Cluster::Mainnet => Some("https://api.mainnet-beta.solana.com"),
+            Cluster::Devnet => Some("https://api.devnet.solana.com"),
+            Cluster::Testnet => Some("https://api.testnet.solana.com"),
+            Cluster::Localnet => Some("http://127.0.0.1:8899"),
+            Cluster::Custom => None
+        }
+    }
+}
+
+/// Commitment level, also accepted as the `commitment` query parameter of the endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromFormField)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized
+}
+
+impl Commitment {
+    pub fn to_commitment_config(self) -> CommitmentConfig {
+        let commitment = match self {
+            Commitment::Processed => CommitmentLevel::Processed,
+            Commitment::Confirmed => CommitmentLevel::Confirmed,
+            Commitment::Finalized => CommitmentLevel::Finalized
+        };
+        CommitmentConfig { commitment }
+    }
+
+    /// getBlock, getTransaction and getSignaturesForAddress do not support the processed commitment level
+    pub fn for_blocks(self) -> Commitment {
+        match self {
+            Commitment::Processed => Commitment::Confirmed,
+            commitment => commitment
+        }
+    }
+}
+
+/// Backend keeping the private keys used for signing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignerKind {
+    /// Keys live in the memory of the process and are lost on restart
+    Memory,
+    /// Keys are encrypted in files, requires `keystore_passphrase`
+    Keystore,
+    /// Keys are held by a remote signing service, requires `remote_signer_url`
+    Remote
+}
+
+impl From<TransactionConfirmationStatus> for Commitment {
+    fn from(confirmation_status: TransactionConfirmationStatus) -> Self {
+        match confirmation_status {
+            TransactionConfirmationStatus::Processed => Commitment::Processed,
+            TransactionConfirmationStatus::Confirmed => Commitment::Confirmed,
+            TransactionConfirmationStatus::Finalized => Commitment::Finalized
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub cluster: Cluster,
+    /// Overrides the default URL of the cluster, mandatory for the custom cluster
+    pub rpc_url: Option<String>,
+    /// Additional endpoints used when the primary one is unhealthy
+    pub fallback_rpc_urls: Vec<String>,
+    pub health_check_interval_secs: u64,
+    /// Endpoints further behind the highest known slot are considered unhealthy
+    pub max_slot_lag: u64,
+    pub commitment: Commitment,
+    /// Timeout of a single RPC request
+    pub timeout_secs: u64,
+    /// Timeout while waiting for the waitFor commitment of a sent transaction
+    pub confirm_timeout_secs: u64,
+    /// Maximum number of slots scanned by a single block range request
+    pub max_block_range: u64,
+    /// Number of blocks fetched in parallel while scanning a range
+    pub block_scan_concurrency: usize,
+    /// Percentile of the recent prioritization fees used by the automatic priority fee
+    pub priority_fee_percentile: u8,
+    /// Upper bound of the automatic priority fee, in micro-lamports per compute unit
+    pub max_priority_fee_micro_lamports: u64,
+    pub signer: SignerKind,
+    /// Directory of the encrypted key files of the keystore signer
+    pub keystore_path: String,
+    pub keystore_passphrase: Option<String>,
+    /// scrypt cost (log2 of N) used when encrypting new keys
+    pub keystore_scrypt_log_n: u8,
+    pub remote_signer_url: Option<String>,
+    /// Accepts private keys in signing requests and returns them from wallet generation
+    pub insecure_private_keys: bool,
+    /// Removed setting, amounts are raw base units with their decimals. Kept to reject configurations still setting it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<u8>
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cluster: Cluster::Devnet,
+            rpc_url: None,
+            fallback_rpc_urls: Vec::new(),
+            health_check_interval_secs: 10,
+            max_slot_lag: 50,
+            commitment: Commitment::Finalized,
+            timeout_secs: 30,
+            confirm_timeout_secs: 60,
+            max_block_range: 500,
+            block_scan_concurrency: 8,
+            priority_fee_percentile: 75,
+            max_priority_fee_micro_lamports: 1_000_000,
+            signer: SignerKind::Memory,
+            keystore_path: "keystore".to_string(),
+            keystore_passphrase: None,
+            keystore_scrypt_log_n: 15,
+            remote_signer_url: None,
+            insecure_private_keys: false,
+            precision: None
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the defaults, the TOML file and the environment, in increasing priority
+    pub fn load() -> Result<Self, ResponseError> {
+        let path = std::env::var(CONFIG_PATH_ENV).unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+
+        let config: Config = Figment::from(Serialized::defaults(Config::default()))
+            .merge(Toml::file(path))
+            .merge(Env::prefixed(CONFIG_ENV_PREFIX))
+            .extract()
+            .map_err(|err| {
+                log::error!("Error while reading the configuration: {}", err);
+                ResponseError::ConfigError(Json(Code{code: "Failed during reading the configuration".to_string()}))
+            })?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ResponseError> {
+        if self.cluster == Cluster::Custom && self.rpc_url.is_none() {
+            log::error!("Error while validating the configuration: custom cluster requires rpc_url");
+            return Err(ResponseError::ConfigError(Json(Code{code: "Custom cluster requires rpc_url".to_string()})));
+        }
+
+        if self.timeout_secs == 0 || self.confirm_timeout_secs == 0 || self.health_check_interval_secs == 0 {
+            log::error!("Error while validating the configuration: timeouts must be positive");
+            return Err(ResponseError::ConfigError(Json(Code{code: "Timeouts must be positive".to_string()})));
+        }
+
+        if self.max_block_range == 0 || self.block_scan_concurrency == 0 {
+            log::error!("Error while validating the configuration: block scan limits must be positive");
+            return Err(ResponseError::ConfigError(Json(Code{code: "Block scan limits must be positive".to_string()})));
+        }
+
+        if self.priority_fee_percentile > 100 {
+            log::error!("Error while validating the configuration: priority fee percentile above 100");
+            return Err(ResponseError::ConfigError(Json(Code{code: "Priority fee percentile cannot be above 100".to_string()})));
+        }
+
+        if self.signer == SignerKind::Keystore && self.keystore_passphrase.as_deref().unwrap_or_default().is_empty() {
+            log::error!("Error while validating the configuration: keystore signer requires keystore_passphrase");
+            return Err(ResponseError::ConfigError(Json(Code{code: "Keystore signer requires keystore_passphrase".to_string()})));
+        }
+
+        if self.signer == SignerKind::Remote && self.remote_signer_url.is_none() {
+            log::error!("Error while validating the configuration: remote signer requires remote_signer_url");
+            return Err(ResponseError::ConfigError(Json(Code{code: "Remote signer requires remote_signer_url".to_string()})));
+        }
+
+        if self.precision.is_some() {
+            log::error!("Error while validating the configuration: precision is no longer supported");
+            return Err(ResponseError::ConfigError(Json(Code{code: "The precision setting was removed, amounts are raw base units with their decimals".to_string()})));
+        }
+
+        Ok(())
+    }
+
+    pub fn rpc_url(&self) -> String {
+        match &self.rpc_url {
+            Some(rpc_url) => rpc_url.clone(),
+            // Custom cluster without rpc_url is rejected by validate
+            None => self.cluster.default_url().unwrap_or_default().to_string()
+        }
+    }
+
+    /// Primary endpoint followed by the fallback ones, without duplicates
+    pub fn rpc_urls(&self) -> Vec<String> {
+        let mut rpc_urls = vec![self.rpc_url()];
+        for rpc_url in &self.fallback_rpc_urls {
+            if !rpc_urls.contains(rpc_url) {
+                rpc_urls.push(rpc_url.clone());
+            }
+        }
+        rpc_urls
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment.to_commitment_config()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn confirm_timeout(&self) -> Duration {
+        Duration::from_secs(self.confirm_timeout_secs)
+    }
+
+    pub fn health_check_interval(&self) -> Duration {
+        Duration::from_secs(self.health_check_interval_secs)
+    }
+}
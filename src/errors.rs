@@ -83,5 +83,89 @@ pub enum ResponseError {
     ConvertUiAmountError(Json<Code>),
 
     #[response(status = 501, content_type = "json")]
-    UiAccountDataTypeError(Json<Code>)
+    UiAccountDataTypeError(Json<Code>),
+
+    #[response(status = 500, content_type = "json")]
+    ConfigError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    SlotSkippedError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidBlockRangeError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    GetBlocksError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    GetMintError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidAmountError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    GetAccountError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    GetEpochError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidPaginationError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    GetSignaturesError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    KeyNotFoundError(Json<Code>),
+
+    #[response(status = 502, content_type = "json")]
+    SignerError(Json<Code>),
+
+    #[response(status = 403, content_type = "json")]
+    InsecureModeError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidSignerError(Json<Code>),
+
+    #[response(status = 501, content_type = "json")]
+    UnsupportedSignerError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidMnemonicError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidDerivationIndexError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidMessageError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidSignatureError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidSendersError(Json<Code>),
+
+    #[response(status = 500, content_type = "json")]
+    GetPrioritizationFeesError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    InvalidComputeBudgetError(Json<Code>),
+
+    #[response(status = 500, content_type = "json")]
+    SimulateTransactionError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    SimulationFailedError(Json<Code>),
+
+    #[response(status = 500, content_type = "json")]
+    GetRentError(Json<Code>),
+
+    #[response(status = 500, content_type = "json")]
+    GetSignatureStatusError(Json<Code>),
+
+    #[response(status = 404, content_type = "json")]
+    TransactionNotFoundError(Json<Code>),
+
+    #[response(status = 400, content_type = "json")]
+    MnemonicRequiredError(Json<Code>)
 }
\ No newline at end of file
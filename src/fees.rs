@@ -0,0 +1,105 @@
+use crate::{config::Config, errors::{ResponseError, Code}, models::{ComputeBudget, PriorityFee}, pool::RpcPool};
+use rocket::serde::json::Json;
+use solana_program::instruction::Instruction;
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, pubkey::Pubkey};
+use std::collections::HashSet;
+
+/// Highest compute unit limit a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Compute units granted per instruction to transactions without compute unit limit
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+/// Percentiles of the recent prioritization fees reported by the fee estimation
+pub const PRIORITY_FEE_PERCENTILES: [u8; 4] = [25, 50, 75, 90];
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Prioritization fees paid in the recent slots by the transactions writing the accounts, in micro-lamports per compute unit
+pub async fn get_recent_prioritization_fees(rpc_pool: &RpcPool, accounts: &[Pubkey]) -> Result<Vec<u64>, ResponseError> {
+    rpc_pool.call(|rpc_client| rpc_client.get_recent_prioritization_fees(accounts)).await
+        .map(|fees| fees.into_iter().map(|fee| fee.prioritization_fee).collect())
+        .map_err(|err| {
+            log::error!("Failed during getting the recent prioritization fees: {}", err);
+            ResponseError::GetPrioritizationFeesError(Json(Code{ code: "Failed during getting the recent prioritization fees".to_string() }))
+        })
+}
+
+/// Nearest-rank percentile of the fees, 0 without fees
+pub fn percentile(fees: &[u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+
+    let mut fees = fees.to_vec();
+    fees.sort_unstable();
+    let rank = (usize::from(percentile) * fees.len()).div_ceil(100).max(1);
+    fees[rank - 1]
+}
+
+/// Price of a compute unit in the auto mode, the configured percentile of the recent fees capped by the configuration
+pub fn auto_compute_unit_price(fees: &[u64], config: &Config) -> u64 {
+    percentile(fees, config.priority_fee_percentile).min(config.max_priority_fee_micro_lamports)
+}
+
+/// Priority fee in lamports, paid on the compute unit limit of the transaction and not on the units it consumes
+pub fn priority_fee(compute_unit_price: u64, compute_unit_limit: u32) -> u64 {
+    let micro_lamports = u128::from(compute_unit_price) * u128::from(compute_unit_limit);
+    u64::try_from(micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT)).unwrap_or(u64::MAX)
+}
+
+/// Compute unit limit of a transaction without compute unit limit instruction
+pub fn default_compute_unit_limit(instruction_count: usize) -> u32 {
+    u32::try_from(instruction_count).unwrap_or(u32::MAX)
+        .saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+        .min(MAX_COMPUTE_UNIT_LIMIT)
+}
+
+/// Accounts written by the instructions and the fee payer, prioritization fees are local to them
+pub fn writable_accounts(fee_payer: &Pubkey, instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts = vec![*fee_payer];
+    let mut seen: HashSet<Pubkey> = HashSet::from([*fee_payer]);
+    for account in instructions.iter().flat_map(|instruction| instruction.accounts.iter()) {
+        if account.is_writable && seen.insert(account.pubkey) {
+            accounts.push(account.pubkey);
+        }
+    }
+    accounts
+}
+
+pub fn check_compute_budget(compute_budget: &ComputeBudget) -> Result<(), ResponseError> {
+    match compute_budget.compute_unit_limit {
+        Some(compute_unit_limit) if compute_unit_limit == 0 || compute_unit_limit > MAX_COMPUTE_UNIT_LIMIT => {
+            Err(ResponseError::InvalidComputeBudgetError(Json(Code{ code: format!("Compute unit limit has to be between 1 and {}", MAX_COMPUTE_UNIT_LIMIT) })))
+        },
+        _ => Ok(())
+    }
+}
+
+/// Instructions setting the compute unit limit and price, to be placed before the instructions of the transaction.
+/// The automatic price is the one of `auto_compute_unit_price` for the recent fees of the written accounts.
+/// The compute budget has to be checked with `check_compute_budget` first
+pub async fn compute_budget_instructions(
+    compute_budget: &ComputeBudget,
+    fee_payer: &Pubkey,
+    instructions: &[Instruction],
+    rpc_pool: &RpcPool,
+    config: &Config
+) -> Result<Vec<Instruction>, ResponseError> {
+    let mut budget_instructions: Vec<Instruction> = Vec::with_capacity(2);
+    if let Some(compute_unit_limit) = compute_budget.compute_unit_limit {
+        budget_instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit));
+    }
+
+    let compute_unit_price = match compute_budget.priority_fee_micro_lamports {
+        Some(PriorityFee::MicroLamports(micro_lamports)) => micro_lamports,
+        Some(PriorityFee::Auto(_)) => {
+            let fees = get_recent_prioritization_fees(rpc_pool, &writable_accounts(fee_payer, instructions)).await?;
+            auto_compute_unit_price(&fees, config)
+        },
+        None => 0
+    };
+    // A price of 0 is the default of the runtime
+    if compute_unit_price > 0 {
+        budget_instructions.push(ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price));
+    }
+
+    Ok(budget_instructions)
+}
@@ -1,11 +1,17 @@
+
 #[macro_use] extern crate rocket;
 
 pub mod errors;
 pub mod blocks;
 pub mod models;
 pub mod config;
+pub mod pool;
 pub mod transactions;
 pub mod wallets;
 pub mod network;
 pub mod server;
-pub mod management;
\ No newline at end of file
+pub mod management;
+pub mod tokens;
+pub mod signers;
+pub mod fees;
+pub mod queries;
\ No newline at end of file
@@ -1,5 +1,4 @@
-use solana_client::rpc_client::RpcClient;
-use blockchain_solana::server;
+use blockchain_solana::{config::Config, pool::{self, RpcPool}, server, signers};
 use std::sync::Arc;
 
 #[rocket::main]
@@ -7,10 +6,31 @@ async fn main() {
     dotenv::dotenv().ok();
     env_logger::init();
 
-    let rpc_url = "https://api.devnet.solana.com".to_string();
-    let rpc_client = Arc::new(RpcClient::new(rpc_url));
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            log::error!("Server could not start, invalid configuration: {:?}", err);
+            return;
+        }
+    };
+    log::info!("Using {:?} cluster at {:?} with {:?} commitment", config.cluster, config.rpc_urls(), config.commitment);
+
+    let rpc_pool = Arc::new(RpcPool::new(&config));
+    pool::spawn_health_check(rpc_pool.clone(), config.health_check_interval());
+
+    let signer = match signers::from_config(&config) {
+        Ok(signer) => signer,
+        Err(err) => {
+            log::error!("Server could not start, invalid signer configuration: {:?}", err);
+            return;
+        }
+    };
+    log::info!("Using {:?} signer", config.signer);
+    if config.insecure_private_keys {
+        log::warn!("Insecure mode: private keys are accepted in signing requests and returned by wallet generation");
+    }
 
-    let rocket = server::start_server(rpc_client).await.unwrap();
+    let rocket = server::start_server(rpc_pool, signer, config).await.unwrap();
 
     match rocket.launch().await {
         Ok(_) => {
@@ -1,4 +1,5 @@
-use crate::{errors::{ResponseError, Code}, models::ArcRwLockPrometheus};
+use crate::{errors::{ResponseError, Code}, models::{ArcRwLockPrometheus, RpcPoolStatus}, pool::RpcPool};
+use std::sync::Arc;
 use rocket::{State, serde::json::Json};
 use prometheus::{Encoder, TextEncoder};
 
@@ -16,4 +17,11 @@ pub fn metrics(
         })?;
     let body = String::from_utf8(buffer.clone()).unwrap();
     Ok(body)
+}
+
+#[get("/rpc/status")]
+pub fn rpc_pool_status(
+    rpc_pool: &State<Arc<RpcPool>>
+) -> Json<RpcPoolStatus> {
+    Json(RpcPoolStatus { endpoints: rpc_pool.status() })
 }
\ No newline at end of file
@@ -1,29 +1,244 @@
 use serde::{Deserialize, Serialize};
-use solana_transaction_status::UiTransactionTokenBalance;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{UiLoadedAddresses, UiTransactionTokenBalance};
 use solana_transaction_status::{EncodedTransactionWithStatusMeta, option_serializer::OptionSerializer, EncodedTransaction, UiMessage};
 use crate::errors::{ResponseError, Code};
-use crate::config::SOL_PRECISION;
-use std::{sync::{Arc, RwLock},time::Instant};
+use crate::config::{Commitment, SOL_PRECISION};
+use std::{collections::{HashMap, HashSet}, str::FromStr, sync::{Arc, RwLock}, time::Instant};
 use prometheus::{opts, HistogramVec, IntCounterVec, Registry};
 use rocket::{
     fairing::{Fairing, Info, Kind},
+    http::Status,
+    request::{FromRequest, Outcome},
     Data, Request, Response,
     serde::json::Json
 };
 
+pub const API_VERSION_HEADER: &str = "X-Api-Version";
+
+/// Response format requested with the X-Api-Version header, version 1 is used when the header is missing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    /// Block height fields hold the slot
+    V1,
+    /// Slot and block height are returned in separate fields
+    V2
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiVersion {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.headers().get_one(API_VERSION_HEADER) {
+            None | Some("1") => Outcome::Success(ApiVersion::V1),
+            Some("2") => Outcome::Success(ApiVersion::V2),
+            Some(version) => {
+                log::error!("Unsupported API version: {}", version);
+                Outcome::Failure((Status::BadRequest, ()))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Block {
-    /// We use slot instead of height in Solana
-    pub height: u64,
+    /// Deprecated, only returned in version 1 where it holds the slot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
     pub hash: String,
-    pub transactions: Vec<TransactionInfo>
+    pub transactions: Vec<TransactionInfo>,
+    pub commitment: Commitment,
+    pub slot: u64,
+    #[serde(rename="blockHeight")]
+    pub block_height: Option<u64>,
+    #[serde(rename="parentSlot")]
+    pub parent_slot: u64,
+    /// Unix timestamp of the block production, if known
+    #[serde(rename="blockTime")]
+    pub block_time: Option<i64>,
+    #[serde(rename="previousBlockhash")]
+    pub previous_blockhash: String
+}
+
+impl Block {
+    pub fn versioned(mut self, version: ApiVersion) -> Self {
+        if version == ApiVersion::V1 {
+            self.height = Some(self.slot);
+        }
+        self.transactions = self.transactions.into_iter()
+            .map(|transaction| transaction.versioned(version))
+            .collect();
+        self
+    }
+}
+
+/// Block fields copied into the transactions of the block
+#[derive(Debug, Clone)]
+pub struct BlockReference {
+    pub slot: u64,
+    pub block_height: Option<u64>,
+    pub block_time: Option<i64>,
+    pub hash: String
+}
+
+/// Keeps the transactions moving funds of watched wallets or mints, everything passes when nothing is watched.
+/// Watched wallets also match the token accounts they own.
+#[derive(Debug, Default)]
+pub struct TransactionFilter {
+    addresses: HashSet<String>,
+    contracts: HashSet<String>
+}
+
+impl TransactionFilter {
+    /// Builds the filter from comma separated address lists
+    pub fn new(addresses: Option<&str>, contracts: Option<&str>) -> Result<Self, ResponseError> {
+        Ok(Self {
+            addresses: parse_address_list(addresses)?,
+            contracts: parse_address_list(contracts)?
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.contracts.is_empty()
+    }
+
+    pub fn matches(&self, transaction: &TransactionInfo) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        transaction.from.iter()
+            .chain(transaction.to.iter())
+            .any(|account| {
+                self.addresses.contains(&account.adress)
+                    || account.owner.as_ref().is_some_and(|owner| self.addresses.contains(owner))
+                    || account.contract.as_ref().is_some_and(|contract| self.contracts.contains(contract))
+            })
+    }
+}
+
+fn parse_address_list(addresses: Option<&str>) -> Result<HashSet<String>, ResponseError> {
+    addresses.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(|address| {
+            Pubkey::from_str(address)
+                .map(|_| address.to_string())
+                .map_err(|err| {
+                    log::error!("Error while creating the Pubkey object from the filter address {}: {}", address, err);
+                    ResponseError::CreatePubkeyError(Json(Code{code: format!("Failed during creating the Pubkey object from the filter address {}", address)}))
+                })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockRange {
+    pub from: u64,
+    /// Last scanned slot, can be lower than requested when the range goes past the latest slot
+    pub to: u64,
+    /// Produced blocks of the range in slot order
+    pub blocks: Vec<Block>,
+    /// Slots of the range without a block
+    #[serde(rename="skippedSlots")]
+    pub skipped_slots: Vec<u64>,
+    pub commitment: Commitment
+}
+
+/// Exact amount of an asset, kept in its smallest unit (lamports for SOL) together with its decimals
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "UiAmount", try_from = "UiAmount")]
+pub struct Amount {
+    pub amount: u64,
+    pub decimals: u8,
+    /// UI amount given by the request, or including the interest of interest-bearing mints.
+    /// The amount divided by 10^decimals when not set.
+    pub ui_amount_string: Option<String>
+}
+
+impl Amount {
+    pub fn new(amount: u64, decimals: u8) -> Self {
+        Self { amount, decimals, ui_amount_string: None }
+    }
+
+    pub fn with_ui_amount_string(mut self, ui_amount_string: String) -> Self {
+        self.ui_amount_string = Some(ui_amount_string);
+        self
+    }
+
+    pub fn ui_amount_string(&self) -> String {
+        match &self.ui_amount_string {
+            Some(ui_amount_string) => ui_amount_string.clone(),
+            None => spl_token::amount_to_ui_amount_string_trimmed(self.amount, self.decimals)
+        }
+    }
+}
+
+/// JSON form of Amount: the raw amount is a string so that it is not rounded by JSON parsers.
+/// Requests may give the decimal uiAmountString instead of the raw amount.
+#[derive(Debug, Serialize, Deserialize)]
+struct UiAmount {
+    #[serde(default)]
+    amount: Option<String>,
+    decimals: u8,
+    #[serde(default, rename="uiAmountString")]
+    ui_amount_string: Option<String>
+}
+
+impl From<Amount> for UiAmount {
+    fn from(amount: Amount) -> Self {
+        Self {
+            amount: Some(amount.amount.to_string()),
+            decimals: amount.decimals,
+            ui_amount_string: Some(amount.ui_amount_string())
+        }
+    }
+}
+
+impl TryFrom<UiAmount> for Amount {
+    type Error = String;
+
+    fn try_from(ui_amount: UiAmount) -> Result<Self, Self::Error> {
+        let from_ui_amount_string = match &ui_amount.ui_amount_string {
+            Some(ui_amount_string) => Some(spl_token::try_ui_amount_into_amount(ui_amount_string.clone(), ui_amount.decimals)
+                .map_err(|_| format!("invalid uiAmountString {} with {} decimals", ui_amount_string, ui_amount.decimals))?),
+            None => None
+        };
+        let from_amount = match ui_amount.amount {
+            Some(amount) => Some(amount.parse::<u64>().map_err(|_| format!("invalid amount {}", amount))?),
+            None => None
+        };
+
+        match (from_amount, from_ui_amount_string) {
+            (Some(amount), Some(ui_amount_string)) if amount != ui_amount_string => Err("amount and uiAmountString differ".to_string()),
+            (Some(amount), _) | (None, Some(amount)) => Ok(Amount {
+                amount,
+                decimals: ui_amount.decimals,
+                // Kept so that mints whose UI amount is not amount / 10^decimals can reject it
+                ui_amount_string: ui_amount.ui_amount_string
+            }),
+            (None, None) => Err("either amount or uiAmountString is required".to_string())
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AccountInfo {
     pub adress: String,
-    pub amount: f64,
-    pub contract: Option<String>
+    pub amount: Amount,
+    pub contract: Option<String>,
+    /// Wallet owning the token account, only set on token balance changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>
+}
+
+/// Mint of a token account and the wallet owning it
+#[derive(Debug, Clone)]
+pub struct TokenBalanceOwner {
+    pub mint: String,
+    pub owner: Option<String>
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,20 +247,35 @@ pub struct TransactionInfo {
     pub to: Vec<AccountInfo>,
     pub hash: String,
     pub status: String,
-    pub fee: f64,
+    pub fee: Amount,
     #[serde(rename="blockHash")]
     pub block_hash: String,
-    /// We use slot instead of height in Solana
+    /// Holds the slot in version 1
     #[serde(rename="blockHeight")]
-    pub block_height: u64
+    pub block_height: Option<u64>,
+    pub slot: u64,
+    #[serde(rename="blockTime")]
+    pub block_time: Option<i64>,
+    /// Only set when the transaction is returned on its own, blocks carry the commitment for all their transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<Commitment>
+}
+
+impl TransactionInfo {
+    pub fn versioned(mut self, version: ApiVersion) -> Self {
+        if version == ApiVersion::V1 {
+            self.block_height = Some(self.slot);
+        }
+        self
+    }
 }
 
 pub trait TransactionInfoConvertiable {
-  fn to_transaction_info(&self, block_slot: u64, hash: &str) -> Result<TransactionInfo, ResponseError>;
+  fn to_transaction_info(&self, block: &BlockReference) -> Result<TransactionInfo, ResponseError>;
 }
 
 impl TransactionInfoConvertiable for EncodedTransactionWithStatusMeta {
-  fn to_transaction_info(&self, block_slot: u64, block_hash: &str) -> Result<TransactionInfo, ResponseError> {
+  fn to_transaction_info(&self, block: &BlockReference) -> Result<TransactionInfo, ResponseError> {
     let meta = match &self.meta {
         Some(meta) => {
             meta
@@ -61,58 +291,58 @@ impl TransactionInfoConvertiable for EncodedTransactionWithStatusMeta {
         "Failed".to_string()
     };
 
-    let transaction_fee = adjust_precision(meta.fee as f64);
-
-    let transaction = match &self.transaction {
-        EncodedTransaction::LegacyBinary(_legacy_binary) => {
-            return Err(ResponseError::EncodedTransactionTypeError(Json(Code{ code: "Encoded transaction type LegacyBinary not implemented".to_string() })));
+    let transaction_fee = Amount::new(meta.fee, SOL_PRECISION);
+
+    // Parsed messages already list the accounts loaded from lookup tables, the other formats only the static ones
+    let (signatures, account_keys) = match &self.transaction {
+        EncodedTransaction::Json(ui_transaction) => {
+            let account_keys = match &ui_transaction.message {
+                UiMessage::Parsed(ui_parsed_message) => ui_parsed_message.account_keys.iter()
+                    .map(|account| account.pubkey.clone())
+                    .collect(),
+                UiMessage::Raw(ui_raw_message) => resolve_account_keys(&ui_raw_message.account_keys, &meta.loaded_addresses)
+            };
+            (ui_transaction.signatures.clone(), account_keys)
         },
-        EncodedTransaction::Binary(_binary, _encoding) => {
-            return Err(ResponseError::EncodedTransactionTypeError(Json(Code{ code: "Encoded transaction type Binary not implemented".to_string() })));
+        EncodedTransaction::LegacyBinary(_) | EncodedTransaction::Binary(_, _) => {
+            let versioned_transaction = self.transaction.decode()
+                .ok_or_else(|| ResponseError::EncodedTransactionTypeError(Json(Code{ code: "Failed during decoding the binary transaction".to_string() })))?;
+            let static_keys: Vec<String> = versioned_transaction.message.static_account_keys().iter()
+                .map(|account_key| account_key.to_string())
+                .collect();
+            let signatures = versioned_transaction.signatures.iter()
+                .map(|signature| signature.to_string())
+                .collect();
+            (signatures, resolve_account_keys(&static_keys, &meta.loaded_addresses))
         },
         EncodedTransaction::Accounts(_ui_accounts_list) => {
             return Err(ResponseError::EncodedTransactionTypeError(Json(Code{ code: "Encoded transaction type Accounts not implemented".to_string() })));
-        },
-        EncodedTransaction::Json(ui_transaction) => ui_transaction
+        }
     };
 
-    let transaction_hash = &transaction.signatures[0];
+    let transaction_hash = match signatures.first() {
+        Some(signature) => signature,
+        None => {
+            return Err(ResponseError::IndexError (Json(Code{code: "Transaction has no signature".to_string() })));
+        }
+    };
 
     let mut transaction_from: Vec<AccountInfo> = Vec::new();
     let mut transaction_to: Vec<AccountInfo> = Vec::new();
 
-    let message = match &transaction.message {
-        UiMessage::Parsed(_ui_parsed_message) => {
-            return Err(ResponseError::TransactionMessageTypeError (Json(Code{ code: "Transaction message type Parsed not implemented".to_string() })));
-        },
-        UiMessage::Raw(ui_raw_message) => ui_raw_message
-    };
-
-    let account_keys = &message.account_keys;
-
     let pre_balances = &meta.pre_balances;
     let post_balances = &meta.post_balances;
     let empty_vec = Vec::<UiTransactionTokenBalance>::new();
 
     for (i, account_key) in account_keys.iter().enumerate() {
-        let diff = post_balances[i] as f64 - pre_balances[i] as f64;
-        let amount: f64 = adjust_precision(diff);
-
-        if amount < 0.0 {
-            transaction_from.push(AccountInfo {
-                adress: account_key.clone(),
-                amount: -amount,
-                /// For SOL transactions, contract is None
-                contract: None 
-            })
-        } else if amount > 0.0 {
-            transaction_to.push(AccountInfo {
-                adress: account_key.clone(),
-                amount,
-                /// For SOL transactions, contract is None
-                contract: None 
-            })
-        }
+        let (pre_balance, post_balance) = match (pre_balances.get(i), post_balances.get(i)) {
+            (Some(pre_balance), Some(post_balance)) => (*pre_balance, *post_balance),
+            _ => {
+                return Err(ResponseError::IndexError (Json(Code{code: "Index out of bounds for balances vector".to_string() })));
+            }
+        };
+        // For SOL transactions, contract is None
+        push_balance_change(&mut transaction_from, &mut transaction_to, account_key, pre_balance, post_balance, SOL_PRECISION, None);
     }
 
     let pre_token_balances = match &meta.pre_token_balances {
@@ -126,58 +356,29 @@ impl TransactionInfoConvertiable for EncodedTransactionWithStatusMeta {
         OptionSerializer::Skip => &empty_vec,
     };
 
-    for post_token_balance in post_token_balances.iter() {
-        let account_index = post_token_balance.account_index as usize;
-        let mint = post_token_balance.clone().mint;
-        let mut amount = match post_token_balance.ui_token_amount.ui_amount {
-            Some(ui_amount) => {
-                ui_amount
-            },
+    // Token accounts closed by the transaction only have a pre token balance
+    let mut token_accounts: Vec<&UiTransactionTokenBalance> = Vec::new();
+    for token_balance in post_token_balances.iter().chain(pre_token_balances.iter()) {
+        if !token_accounts.iter().any(|token_account| token_account.account_index == token_balance.account_index && token_account.mint == token_balance.mint) {
+            token_accounts.push(token_balance);
+        }
+    }
+
+    for token_balance in token_accounts {
+        let (account_index, mint) = (token_balance.account_index, &token_balance.mint);
+        let pre_amount = token_balance_amount(pre_token_balances, account_index, mint)?;
+        let post_amount = token_balance_amount(post_token_balances, account_index, mint)?;
+        let adress = match account_keys.get(account_index as usize) {
+            Some(address) => address,
             None => {
-                return Err(ResponseError::BalanceAmountError (Json(Code { code: "Failed during getting the amount from token balance".to_string() })));
+                return Err(ResponseError::IndexError (Json(Code{code: "Index out of bounds for account_keys vector".to_string() })));
             }
         };
-
-        for pre_token_balance in pre_token_balances.iter() {
-            if pre_token_balance.account_index as usize == account_index && pre_token_balance.mint == mint {
-                amount -= match pre_token_balance.ui_token_amount.ui_amount {
-                    Some(ui_amount) => {
-                        ui_amount
-                    },
-                    None => {
-                        return Err(ResponseError::BalanceAmountError (Json(Code { code: "Failed during getting the amount from token balance".to_string() })));
-                    }
-                };
-            }
-        }
-
-        if amount < 0.0 {
-            transaction_from.push(AccountInfo {
-                adress: match account_keys.get(account_index) {
-                    Some(address) => {
-                        address.clone()
-                    },
-                    None => {
-                        return Err(ResponseError::IndexError (Json(Code{code: "Index out of bounds for account_keys vector".to_string() })));
-                    }
-                },
-                amount: -amount,
-                contract: Some(mint)
-            })
-        } else if amount > 0.0 {
-            transaction_to.push(AccountInfo {
-                adress: match account_keys.get(account_index) {
-                    Some(address) => {
-                        address.clone()
-                    },
-                    None => {
-                        return Err(ResponseError::IndexError (Json(Code { code: "Index out of bounds for account_keys vector".to_string() })));
-                    }
-                },
-                amount,
-                contract: Some(mint)
-            })
-        }
+        let token = TokenBalanceOwner {
+            mint: mint.clone(),
+            owner: Option::<String>::from(token_balance.owner.clone())
+        };
+        push_balance_change(&mut transaction_from, &mut transaction_to, adress, pre_amount, post_amount, token_balance.ui_token_amount.decimals, Some(token));
     }
 
     Ok(TransactionInfo {
@@ -186,14 +387,67 @@ impl TransactionInfoConvertiable for EncodedTransactionWithStatusMeta {
         hash: transaction_hash.to_string(),
         status: transaction_status,
         fee: transaction_fee,
-        block_hash: block_hash.to_string(),
-        block_height: block_slot
+        block_hash: block.hash.clone(),
+        block_height: block.block_height,
+        slot: block.slot,
+        block_time: block.block_time,
+        commitment: None
     })
   }
 }
 
-pub fn adjust_precision(val: f64) -> f64 {
-    (val) / (10_u32.pow(SOL_PRECISION) as f64)
+/// Account keys in the order used by the balance indices: static keys of the message,
+/// then the writable and readonly addresses loaded from lookup tables by v0 transactions
+pub fn resolve_account_keys(static_keys: &[String], loaded_addresses: &OptionSerializer<UiLoadedAddresses>) -> Vec<String> {
+    let mut account_keys = static_keys.to_vec();
+    if let OptionSerializer::Some(loaded_addresses) = loaded_addresses {
+        account_keys.extend(loaded_addresses.writable.iter().cloned());
+        account_keys.extend(loaded_addresses.readonly.iter().cloned());
+    }
+    account_keys
+}
+
+/// Raw amount of the token account in the given balances, accounts missing from them hold nothing
+fn token_balance_amount(token_balances: &[UiTransactionTokenBalance], account_index: u8, mint: &str) -> Result<u64, ResponseError> {
+    match token_balances.iter().find(|token_balance| token_balance.account_index == account_index && token_balance.mint == mint) {
+        Some(token_balance) => token_balance.ui_token_amount.amount.parse::<u64>()
+            .map_err(|err| {
+                log::error!("Error while parsing the token balance amount: {}", err);
+                ResponseError::BalanceAmountError (Json(Code { code: "Failed during getting the amount from token balance".to_string() }))
+            }),
+        None => Ok(0)
+    }
+}
+
+pub fn push_balance_change(
+    transaction_from: &mut Vec<AccountInfo>,
+    transaction_to: &mut Vec<AccountInfo>,
+    adress: &str,
+    pre_balance: u64,
+    post_balance: u64,
+    decimals: u8,
+    token: Option<TokenBalanceOwner>
+) {
+    let (contract, owner) = match token {
+        Some(token) => (Some(token.mint), token.owner),
+        None => (None, None)
+    };
+
+    if post_balance < pre_balance {
+        transaction_from.push(AccountInfo {
+            adress: adress.to_string(),
+            amount: Amount::new(pre_balance - post_balance, decimals),
+            contract,
+            owner
+        })
+    } else if post_balance > pre_balance {
+        transaction_to.push(AccountInfo {
+            adress: adress.to_string(),
+            amount: Amount::new(post_balance - pre_balance, decimals),
+            contract,
+            owner
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -203,10 +457,51 @@ pub struct SendTransactionRequest {
 }
 
 
+/// Signed transaction like in the send endpoint, or a message returned by the build endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulateTransactionRequest {
+    #[serde(default, rename="signedTransaction", skip_serializing_if = "Option::is_none")]
+    pub signed_transaction: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationResult {
+    /// Balance changes between the current and the simulated state of the accounts, like in the transaction details
+    pub from: Vec<AccountInfo>,
+    pub to: Vec<AccountInfo>,
+    pub status: String,
+    /// Reason of the failure, custom errors of the known programs are decoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    #[serde(rename="unitsConsumed")]
+    pub units_consumed: u64
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SendTransactionResponse {
   #[serde(rename="txnHash")]
     pub txn_hash: String,
+    /// Only with waitFor, the last known status when the commitment is not reached in time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<TransactionStatus>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionStatus {
+    #[serde(rename="txnHash")]
+    pub txn_hash: String,
+    pub slot: u64,
+    /// Blocks confirmed on top of the block of the transaction, not set once it is finalized
+    pub confirmations: Option<usize>,
+    #[serde(rename="confirmationStatus")]
+    pub confirmation_status: Option<Commitment>,
+    /// Success or Failed, like in the transaction details
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>
 }
 
 
@@ -214,37 +509,331 @@ pub struct SendTransactionResponse {
 pub struct SignTransactionRequest {
     pub from: Vec<AccountInfo>,
     pub to: Vec<AccountInfo>,
-    pub private_key: String
+    /// Key of the first sender in the signer backend
+    #[serde(default, rename="keyId", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// Only accepted in insecure mode, key of the first sender
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    /// Creates the associated token accounts of the recipients that do not have one yet, paid by the fee payer
+    #[serde(default, rename="createAssociatedTokenAccount")]
+    pub create_associated_token_account: bool,
+    /// Pays the fees instead of the first sender
+    #[serde(default, rename="feePayer", skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<String>,
+    /// Keys of the other signers in the signer backend by address, signers left out are looked up by address
+    #[serde(default, rename="keyIds", skip_serializing_if = "HashMap::is_empty")]
+    pub key_ids: HashMap<String, String>,
+    /// Signs with the keys available and lists the missing signatures instead of failing
+    #[serde(default)]
+    pub partial: bool,
+    #[serde(flatten)]
+    pub compute_budget: ComputeBudget
+}
+
+/// Compute budget instructions prepended to the transfers, none are added by default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComputeBudget {
+    /// Compute units the transaction may consume, the runtime default applies without it
+    #[serde(default, rename="computeUnitLimit", skip_serializing_if = "Option::is_none")]
+    pub compute_unit_limit: Option<u32>,
+    #[serde(default, rename="priorityFeeMicroLamports", skip_serializing_if = "Option::is_none")]
+    pub priority_fee_micro_lamports: Option<PriorityFee>
+}
+
+/// Price of a compute unit in micro-lamports, or `auto` to follow the recent prioritization fees of the written accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PriorityFee {
+    MicroLamports(u64),
+    Auto(AutoPriorityFee)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoPriorityFee {
+    Auto
 }
 
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildTransactionRequest {
+    pub from: Vec<AccountInfo>,
+    pub to: Vec<AccountInfo>,
+    #[serde(default, rename="createAssociatedTokenAccount")]
+    pub create_associated_token_account: bool,
+    #[serde(default, rename="feePayer", skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<String>,
+    #[serde(flatten)]
+    pub compute_budget: ComputeBudget
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildTransactionResponse {
+    /// Base64 encoded message, the bytes to sign
+    pub message: String,
+    /// Addresses that have to sign, in the order of the signatures
+    #[serde(rename="requiredSigners")]
+    pub required_signers: Vec<String>,
+    #[serde(rename="recentBlockhash")]
+    pub recent_blockhash: String,
+    /// The transaction is rejected once the chain is past this height
+    #[serde(rename="lastValidBlockHeight")]
+    pub last_valid_block_height: u64
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CombineTransactionRequest {
+    /// Base64 encoded message returned by the build endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Partially signed transaction returned by the sign or combine endpoints, instead of the message
+    #[serde(default, rename="signedTransaction", skip_serializing_if = "Option::is_none")]
+    pub signed_transaction: Option<String>,
+    pub signatures: Vec<ExternalSignature>,
+    /// Lists the missing signatures instead of failing
+    #[serde(default)]
+    pub partial: bool
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExternalSignature {
+    #[serde(rename="publicKey")]
+    pub public_key: String,
+    pub signature: String
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SignTransactionResponse {
     #[serde(rename="signedTransaction")]
     pub signed_transaction: String,
-    #[serde(rename="txnHash")]
-    pub txn_hash: String
+    /// Signature of the fee payer, not known before the fee payer has signed
+    #[serde(rename="txnHash", skip_serializing_if = "Option::is_none")]
+    pub txn_hash: Option<String>,
+    /// Addresses that still have to sign a partially signed transaction
+    #[serde(default, rename="missingSignatures", skip_serializing_if = "Vec::is_empty")]
+    pub missing_signatures: Vec<String>
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Balance {
-    pub balance: f64
+    /// Sum of all the token accounts of the mint for token balances
+    pub balance: Amount,
+    /// Token accounts of the wallet for the mint, not set for SOL balances
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<Vec<TokenAccountBalance>>,
+    pub commitment: Commitment
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub address: String,
+    /// SOL balance of the wallet
+    pub balance: Amount,
+    /// Holdings of every mint of the SPL Token and Token-2022 programs
+    pub tokens: Vec<TokenHolding>,
+    pub commitment: Commitment
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenHolding {
+    pub contract: String,
+    #[serde(rename="programId")]
+    pub program_id: String,
+    /// Sum of the token accounts of the mint
+    pub balance: Amount,
+    pub accounts: Vec<TokenAccountBalance>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddressTransactions {
+    pub address: String,
+    pub transactions: Vec<AddressTransaction>,
+    /// Value of `before` for the next page, not set once the oldest transaction is reached
+    #[serde(rename="nextBefore")]
+    pub next_before: Option<String>,
+    pub commitment: Commitment
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddressTransaction {
+    pub hash: String,
+    pub slot: u64,
+    #[serde(rename="blockTime")]
+    pub block_time: Option<i64>,
+    pub status: String,
+    #[serde(rename="confirmationStatus")]
+    pub confirmation_status: Option<Commitment>,
+    /// Only set with includeTransactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<TransactionInfo>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenAccountBalance {
+    pub address: String,
+    pub amount: Amount,
+    pub frozen: bool,
+    pub delegate: Option<String>
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreateWalletRequest {
+    #[serde(default)]
+    pub label: Option<String>
+}
+
+/// Key of the signer backend, the private key never leaves the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyInfo {
+    #[serde(rename="keyId")]
+    pub key_id: String,
+    pub address: String,
+    pub label: Option<String>,
+    /// Archived keys are kept but cannot sign anymore
+    pub archived: bool,
+    /// Creation time in seconds since the epoch, unknown for remote keys
+    #[serde(rename="createdAt")]
+    pub created_at: Option<u64>,
+    /// HD wallet the key was derived from
+    #[serde(rename="hdWallet", skip_serializing_if = "Option::is_none")]
+    pub hd_wallet: Option<String>,
+    #[serde(rename="derivationIndex", skip_serializing_if = "Option::is_none")]
+    pub derivation_index: Option<u32>,
+    #[serde(rename="derivationPath", skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyList {
+    pub keys: Vec<KeyInfo>
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreateHdWalletRequest {
+    /// BIP39 mnemonic to import, a new one is generated when left out
+    #[serde(default)]
+    pub mnemonic: Option<String>,
+    /// Optional BIP39 passphrase
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>
+}
+
+/// Seed kept by the signer backend, the addresses are derived at m/44'/501'/index'/0'
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HdWalletInfo {
+    /// Address at index 0, the same seed is always the same wallet
+    #[serde(rename="walletId")]
+    pub wallet_id: String,
+    pub label: Option<String>,
+    /// Index derived when no index is given
+    #[serde(rename="nextIndex")]
+    pub next_index: u32,
+    /// Only returned for generated mnemonics in insecure mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DeriveAddressRequest {
+    /// Defaults to the next index of the wallet, lower indexes can be derived again to recover the addresses
+    #[serde(default)]
+    pub index: Option<u32>,
+    #[serde(default)]
+    pub label: Option<String>
+}
+
+/// Fields left out are not changed, an empty label removes the label
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateKeyRequest {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub archived: Option<bool>
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletResponse {
     pub address: String,
-    #[serde(rename="privateKey")]
-    pub private_key: String
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Reference of the key in the signer backend
+    #[serde(rename="keyId", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// Only returned in insecure mode
+    #[serde(rename="privateKey", skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ContractResponse {
     #[serde(rename="calculatedFee")]
     pub calculated_fee: u64
 }
+
+/// Fees of a transfer in lamports, except for the prices in micro-lamports per compute unit
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Fee of the signatures
+    #[serde(rename="baseFee")]
+    pub base_fee: u64,
+    /// Compute units consumed by the transfers in simulation
+    #[serde(rename="computeUnits")]
+    pub compute_units: u64,
+    /// Compute unit limit the priority fee is paid on, the requested one or the default of the runtime
+    #[serde(rename="computeUnitLimit")]
+    pub compute_unit_limit: u32,
+    /// Requested price of a compute unit, 0 without priority fee
+    #[serde(rename="computeUnitPrice")]
+    pub compute_unit_price: u64,
+    #[serde(rename="priorityFee")]
+    pub priority_fee: u64,
+    /// Recent prioritization fees of the written accounts
+    #[serde(rename="priorityFeePercentiles")]
+    pub priority_fee_percentiles: Vec<PriorityFeePercentile>,
+    /// Rent exemption of the created associated token accounts, paid by the fee payer
+    pub rent: u64,
+    #[serde(rename="createdAccounts")]
+    pub created_accounts: Vec<String>,
+    /// Base fee, priority fee and rent
+    pub total: u64
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriorityFeePercentile {
+    pub percentile: u8,
+    #[serde(rename="computeUnitPrice")]
+    pub compute_unit_price: u64,
+    /// Priority fee at this price on the compute unit limit
+    #[serde(rename="priorityFee")]
+    pub priority_fee: u64
+}
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfirmationCount {
     #[serde(rename="confirmationsCount")]
-    pub confirmations_count: u64
+    pub confirmations_count: u64,
+    pub commitment: Commitment
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcEndpointStatus {
+    pub url: String,
+    pub healthy: bool,
+    pub slot: Option<u64>,
+    #[serde(rename="slotLag")]
+    pub slot_lag: Option<u64>,
+    #[serde(rename="latencyMs")]
+    pub latency_ms: Option<u64>,
+    #[serde(rename="lastError")]
+    pub last_error: Option<String>,
+    /// Unix timestamp of the last health check
+    #[serde(rename="lastChecked")]
+    pub last_checked: Option<u64>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcPoolStatus {
+    pub endpoints: Vec<RpcEndpointStatus>
 }
 
 pub struct PrometheusMetrics{
@@ -1,16 +1,29 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use rocket::{State, serde::json::Json};
-use crate::{errors::{ResponseError, Code}, models::ContractResponse};
-use spl_token::instruction::transfer;
+use crate::{
+    config::Config,
+    errors::{ResponseError, Code},
+    fees::{auto_compute_unit_price, check_compute_budget, default_compute_unit_limit, get_recent_prioritization_fees, percentile, priority_fee, writable_accounts, MAX_COMPUTE_UNIT_LIMIT, PRIORITY_FEE_PERCENTILES},
+    models::{BuildTransactionRequest, ContractResponse, FeeEstimate, PriorityFee, PriorityFeePercentile},
+    pool::RpcPool,
+    tokens::get_mint,
+    transactions::{build_transfer_instructions, describe_transaction_error, get_latest_blockhash, parse_transfer_accounts, TransferInstructions}
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
 use solana_program::instruction::Instruction;
-use solana_sdk::{message::Message, pubkey::Pubkey};
-use solana_client::rpc_client::RpcClient;
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, message::Message, pubkey::Pubkey, transaction::Transaction};
 use std::str::FromStr;
 
-fn calculate_fee(instruction: Instruction, rpc_client: Arc<RpcClient>, sender_address: Option<&Pubkey>) -> Result<Json<ContractResponse>, ResponseError>{
+async fn calculate_fee(instruction: Instruction, rpc_pool: Arc<RpcPool>, config: &Config, sender_address: Option<&Pubkey>) -> Result<Json<ContractResponse>, ResponseError>{
     let instructions: [Instruction; 1] = [instruction];
-    let message: Message = Message::new_with_blockhash(&instructions, sender_address, &rpc_client.get_latest_blockhash().unwrap());
-    let calculated_fee = rpc_client.get_fee_for_message(&message)
+    let (blockhash, _) = rpc_pool.call(|rpc_client| rpc_client.get_latest_blockhash_with_commitment(config.commitment())).await
+    .map_err(|err| {
+        log::error!("Error while getting the latest blockhash: {}", err);
+        ResponseError::GetBlockhashError(Json(Code{code: "Failed during getting the latest blockhash".to_string()}))
+    })?;
+    let message: Message = Message::new_with_blockhash(&instructions, sender_address, &blockhash);
+    let calculated_fee = rpc_pool.call(|rpc_client| rpc_client.get_fee_for_message(&message)).await
     .map_err(|err| {
         log::error!("Error while getting the fee: {}", err);
         ResponseError::GetFeeError(Json(Code {code: "Error while getting the fee".to_string()}))
@@ -19,9 +32,10 @@ fn calculate_fee(instruction: Instruction, rpc_client: Arc<RpcClient>, sender_ad
 }
 
 #[get("/fee/estimate?<contract>")]
-pub fn get_calculated_fee(
+pub async fn get_calculated_fee(
     contract: Option<String>,
-    rpc_client: &State<Arc<RpcClient>>
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>
 ) -> Result<Json<ContractResponse>, ResponseError> {
     let sender_address: Pubkey = Pubkey::new_unique();
     let to_address: Pubkey = Pubkey::new_unique();
@@ -35,22 +49,105 @@ pub fn get_calculated_fee(
             })?;
 
 
-            let instruction: Instruction = transfer(
-            &spl_token::id(),
-            &contract_address,
-            &to_address,
-            &sender_address,
-            &[&sender_address],
-            1)
-            .map_err(|err| {
-                log::error!("Error while creating the transfer: {}", err);
-                ResponseError::CreateTransferError(Json(Code{code: "Error while creating the transfer".to_string()}))
-            })?;
-            calculate_fee(instruction, rpc_client.inner().clone(), Some(&sender_address))
+            // The token program of the mint and its transfer fee extension change the instruction
+            let mint = get_mint(rpc_pool, &contract_address, config.commitment).await?;
+            let source = get_associated_token_address_with_program_id(&sender_address, &contract_address, &mint.program_id);
+            let destination = get_associated_token_address_with_program_id(&to_address, &contract_address, &mint.program_id);
+            let instruction: Instruction = mint.transfer_checked(&source, &destination, &sender_address, 1, 0)?;
+            calculate_fee(instruction, rpc_pool.inner().clone(), config, Some(&sender_address)).await
         },
         None => {
             let instruction: Instruction = solana_sdk::system_instruction::transfer(&sender_address, &to_address, 100000000);
-            calculate_fee(instruction, rpc_client.inner().clone(), None)
+            calculate_fee(instruction, rpc_pool.inner().clone(), config, None).await
         }
     }
 }
+
+/// Fees of the transfers described like in the sign endpoint, the transfers are simulated for their compute units
+#[post("/fee/estimate", data = "<transaction_parameters>")]
+pub async fn estimate_transfer_fee(
+    transaction_parameters: Json<BuildTransactionRequest>,
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>
+) -> Result<Json<FeeEstimate>, ResponseError> {
+    let accounts = parse_transfer_accounts(&transaction_parameters.from, &transaction_parameters.to, transaction_parameters.fee_payer.as_deref())?;
+    let compute_budget = &transaction_parameters.compute_budget;
+    check_compute_budget(compute_budget)?;
+
+    let (blockhash, _) = get_latest_blockhash(rpc_pool, config).await?;
+    let TransferInstructions { instructions, created_accounts } = build_transfer_instructions(&accounts, &transaction_parameters.to, transaction_parameters.create_associated_token_account, rpc_pool, config).await?;
+
+    // The highest limit lets the simulation measure the transfers, without price the message only pays the base fee
+    let mut simulated_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT)];
+    simulated_instructions.extend(instructions.iter().cloned());
+    let message = Message::new_with_blockhash(&simulated_instructions, Some(&accounts.fee_payer), &blockhash);
+
+    let base_fee = rpc_pool.call(|rpc_client| rpc_client.get_fee_for_message(&message)).await
+        .map_err(|err| {
+            log::error!("Error while getting the fee: {}", err);
+            ResponseError::GetFeeError(Json(Code {code: "Error while getting the fee".to_string()}))
+        })?;
+
+    // The transaction is not signed yet, the signatures are not verified
+    let transaction = Transaction::new_unsigned(message);
+    let simulation_config = RpcSimulateTransactionConfig {sig_verify: false, replace_recent_blockhash: true, commitment: Some(config.commitment()), ..RpcSimulateTransactionConfig::default()};
+    let simulation = rpc_pool.call(|rpc_client| rpc_client.simulate_transaction_with_config(&transaction, simulation_config.clone())).await
+        .map(|response| response.value)
+        .map_err(|err| {
+            log::error!("Failed during simulating the transaction: {}", err);
+            ResponseError::SimulateTransactionError(Json(Code {code: "Failed during simulating the transaction".to_string()}))
+        })?;
+    if let Some(err) = simulation.err {
+        return Err(ResponseError::SimulationFailedError(Json(Code {code: format!("Transaction fails in simulation: {}", describe_transaction_error(&err, &transaction.message))})));
+    }
+
+    let fees = get_recent_prioritization_fees(rpc_pool, &writable_accounts(&accounts.fee_payer, &instructions)).await?;
+    let compute_unit_limit = compute_budget.compute_unit_limit.unwrap_or_else(|| default_compute_unit_limit(instructions.len()));
+    let compute_unit_price = match compute_budget.priority_fee_micro_lamports {
+        Some(PriorityFee::MicroLamports(micro_lamports)) => micro_lamports,
+        Some(PriorityFee::Auto(_)) => auto_compute_unit_price(&fees, config),
+        None => 0
+    };
+    let priority_fee_percentiles = PRIORITY_FEE_PERCENTILES.iter()
+        .map(|&fee_percentile| {
+            let compute_unit_price = percentile(&fees, fee_percentile);
+            PriorityFeePercentile {
+                percentile: fee_percentile,
+                compute_unit_price,
+                priority_fee: priority_fee(compute_unit_price, compute_unit_limit)
+            }
+        })
+        .collect();
+
+    // Created accounts of the same mint have the same size
+    let mut rent_by_len: HashMap<usize, u64> = HashMap::new();
+    let mut rent: u64 = 0;
+    for (_, account_len) in created_accounts.iter() {
+        let account_rent = match rent_by_len.get(account_len) {
+            Some(account_rent) => *account_rent,
+            None => {
+                let account_rent = rpc_pool.call(|rpc_client| rpc_client.get_minimum_balance_for_rent_exemption(*account_len)).await
+                    .map_err(|err| {
+                        log::error!("Failed during getting the rent exemption: {}", err);
+                        ResponseError::GetRentError(Json(Code {code: "Failed during getting the rent exemption".to_string()}))
+                    })?;
+                rent_by_len.insert(*account_len, account_rent);
+                account_rent
+            }
+        };
+        rent = rent.saturating_add(account_rent);
+    }
+
+    let priority_fee = priority_fee(compute_unit_price, compute_unit_limit);
+    Ok(Json(FeeEstimate {
+        base_fee,
+        compute_units: simulation.units_consumed.unwrap_or_default(),
+        compute_unit_limit,
+        compute_unit_price,
+        priority_fee,
+        priority_fee_percentiles,
+        rent,
+        created_accounts: created_accounts.iter().map(|(address, _)| address.to_string()).collect(),
+        total: base_fee.saturating_add(priority_fee).saturating_add(rent)
+    }))
+}
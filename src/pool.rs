@@ -0,0 +1,187 @@
+use crate::{config::Config, models::RpcEndpointStatus};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    rpc_request::{RpcError, RpcResponseErrorData}
+};
+use rocket::futures::future::join_all;
+use std::{future::Future, sync::{Arc, RwLock}, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+
+#[derive(Debug, Clone, Default)]
+struct EndpointHealth {
+    healthy: bool,
+    slot: Option<u64>,
+    slot_lag: Option<u64>,
+    latency: Option<Duration>,
+    last_error: Option<String>,
+    last_checked: Option<SystemTime>
+}
+
+struct RpcEndpoint {
+    url: String,
+    client: RpcClient,
+    health: RwLock<EndpointHealth>
+}
+
+impl RpcEndpoint {
+    fn new(url: String, config: &Config) -> Self {
+        let client = RpcClient::new_with_timeouts_and_commitment(
+            url.clone(),
+            config.timeout(),
+            config.commitment(),
+            config.confirm_timeout()
+        );
+        // Endpoints are assumed healthy until the first health check says otherwise
+        let health = EndpointHealth { healthy: true, ..EndpointHealth::default() };
+        Self { url, client, health: RwLock::new(health) }
+    }
+
+    fn mark_unhealthy(&self, err: &ClientError) {
+        let mut health = self.health.write().unwrap();
+        health.healthy = false;
+        health.last_error = Some(err.to_string());
+    }
+}
+
+/// Set of upstream RPC nodes, calls are routed to the healthiest one and retried on the others
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    max_slot_lag: u64
+}
+
+impl RpcPool {
+    pub fn new(config: &Config) -> Self {
+        let endpoints = config.rpc_urls()
+            .into_iter()
+            .map(|url| RpcEndpoint::new(url, config))
+            .collect();
+
+        Self { endpoints, max_slot_lag: config.max_slot_lag }
+    }
+
+    /// Probes every endpoint with getHealth and getSlot and updates their slot lag
+    pub async fn check_health(&self) {
+        let probes: Vec<(ClientResult<u64>, Duration)> = join_all(self.endpoints.iter()
+            .map(|endpoint| async move {
+                let start = Instant::now();
+                let result = match endpoint.client.get_health().await {
+                    Ok(_) => endpoint.client.get_slot().await,
+                    Err(err) => Err(err)
+                };
+                (result, start.elapsed())
+            }))
+            .await;
+
+        let highest_slot = probes.iter()
+            .filter_map(|(result, _)| result.as_ref().ok())
+            .max()
+            .copied();
+
+        for (endpoint, (result, latency)) in self.endpoints.iter().zip(probes) {
+            let mut health = endpoint.health.write().unwrap();
+            health.last_checked = Some(SystemTime::now());
+            health.latency = Some(latency);
+
+            match result {
+                Ok(slot) => {
+                    let slot_lag = highest_slot.unwrap_or(slot).saturating_sub(slot);
+                    health.slot = Some(slot);
+                    health.slot_lag = Some(slot_lag);
+                    health.healthy = slot_lag <= self.max_slot_lag;
+                    health.last_error = if health.healthy {
+                        None
+                    } else {
+                        Some(format!("Endpoint is {} slots behind", slot_lag))
+                    };
+                },
+                Err(err) => {
+                    log::warn!("Health check failed for RPC endpoint {}: {}", endpoint.url, err);
+                    health.healthy = false;
+                    health.slot_lag = None;
+                    health.last_error = Some(err.to_string());
+                }
+            }
+        }
+    }
+
+    /// Endpoints ordered by preference: healthy ones first, then by slot lag, then by configuration order
+    fn ranked(&self) -> Vec<&RpcEndpoint> {
+        let mut ranked: Vec<(usize, &RpcEndpoint, bool, u64)> = self.endpoints.iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let health = endpoint.health.read().unwrap();
+                (index, endpoint, health.healthy, health.slot_lag.unwrap_or(0))
+            })
+            .collect();
+
+        ranked.sort_by_key(|(index, _, healthy, slot_lag)| (!*healthy, *slot_lag, *index));
+        ranked.into_iter().map(|(_, endpoint, _, _)| endpoint).collect()
+    }
+
+    /// Runs the RPC call on the healthiest endpoint, failing over to the next one on connection errors
+    pub async fn call<'a, T, F, Fut>(&'a self, rpc_call: F) -> ClientResult<T>
+    where
+        F: Fn(&'a RpcClient) -> Fut,
+        Fut: Future<Output = ClientResult<T>>
+    {
+        let mut last_error: Option<ClientError> = None;
+
+        for endpoint in self.ranked() {
+            match rpc_call(&endpoint.client).await {
+                Ok(result) => return Ok(result),
+                Err(err) if is_failover_error(&err) => {
+                    log::warn!("RPC endpoint {} failed, trying the next one: {}", endpoint.url, err);
+                    endpoint.mark_unhealthy(&err);
+                    last_error = Some(err);
+                },
+                Err(err) => return Err(err)
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| ClientErrorKind::Custom("No RPC endpoint configured".to_string()).into()))
+    }
+
+    pub fn status(&self) -> Vec<RpcEndpointStatus> {
+        self.endpoints.iter()
+            .map(|endpoint| {
+                let health = endpoint.health.read().unwrap();
+                RpcEndpointStatus {
+                    url: endpoint.url.clone(),
+                    healthy: health.healthy,
+                    slot: health.slot,
+                    slot_lag: health.slot_lag,
+                    latency_ms: health.latency.map(|latency| latency.as_millis() as u64),
+                    last_error: health.last_error.clone(),
+                    last_checked: health.last_checked
+                        .and_then(|checked| checked.duration_since(UNIX_EPOCH).ok())
+                        .map(|checked| checked.as_secs())
+                }
+            })
+            .collect()
+    }
+}
+
+/// Errors caused by the node itself rather than by the request, worth retrying on another node
+fn is_failover_error(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        // Raised when the client could not talk to the node, e.g. while querying its version
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, data, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY || matches!(data, RpcResponseErrorData::NodeUnhealthy { .. })
+        },
+        _ => false
+    }
+}
+
+/// Periodically refreshes the health of the pool endpoints in the background
+pub fn spawn_health_check(rpc_pool: Arc<RpcPool>, period: Duration) {
+    rocket::tokio::spawn(async move {
+        let mut interval = rocket::tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            rpc_pool.check_health().await;
+        }
+    });
+}
@@ -0,0 +1,40 @@
+// The FromForm derive of Rocket 0.5.0-rc.3 allows the private_in_public lint, which newer toolchains removed
+#![allow(renamed_and_removed_lints)]
+
+use crate::config::Commitment;
+
+#[derive(Debug, FromForm)]
+pub struct BlockQuery {
+    pub commitment: Option<Commitment>,
+    #[field(name = "includeTransactions")]
+    pub include_transactions: bool
+}
+
+/// Either `to` or `limit` has to be given
+#[derive(Debug, FromForm)]
+pub struct BlockRangeQuery {
+    pub from: u64,
+    pub to: Option<u64>,
+    pub limit: Option<u64>,
+    pub commitment: Option<Commitment>,
+    pub addresses: Option<String>,
+    pub contracts: Option<String>
+}
+
+/// Signatures are returned newest first, `before` and `until` are signatures bounding the page
+#[derive(Debug, FromForm)]
+pub struct AddressTransactionsQuery {
+    pub before: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+    pub commitment: Option<Commitment>,
+    #[field(name = "includeTransactions")]
+    pub include_transactions: bool
+}
+
+/// Without waitFor the transaction is sent without waiting for any confirmation
+#[derive(Debug, FromForm)]
+pub struct SendTransactionQuery {
+    #[field(name = "waitFor")]
+    pub wait_for: Option<Commitment>
+}
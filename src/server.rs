@@ -3,14 +3,16 @@ use crate::transactions;
 use crate::wallets;
 use crate::network;
 use crate::management;
+use crate::config::Config;
+use crate::pool::RpcPool;
+use crate::signers::SignerBackend;
 
 use rocket::Ignite;
 use rocket::Rocket;
-use solana_client::rpc_client::RpcClient;
 use crate::models::{PrometheusMetrics, ArcRwLockPrometheus};
 use std::sync::{Arc, RwLock};
 
-pub async fn start_server(rpc_client: Arc<RpcClient>) -> Result<Rocket<Ignite>, ()>{
+pub async fn start_server(rpc_pool: Arc<RpcPool>, signer: Arc<dyn SignerBackend>, config: Config) -> Result<Rocket<Ignite>, ()>{
     let prometheus = Arc::new(RwLock::new(PrometheusMetrics::new("blockchain_solana").unwrap()));
     let prometheus_fairing = ArcRwLockPrometheus::new(prometheus.clone());
 
@@ -18,18 +20,33 @@ pub async fn start_server(rpc_client: Arc<RpcClient>) -> Result<Rocket<Ignite>,
             .mount("/", routes![
             blocks::get_latest_block,
             blocks::scan_block_transactions_from_slot,
+            blocks::scan_block_range,
             transactions::sign_transaction,
+            transactions::build_transaction,
+            transactions::combine_transaction,
             transactions::send_transaction,
+            transactions::simulate_transaction,
+            transactions::get_transaction_status,
             transactions::get_transaction_details,
             transactions::get_confirmation_count,
             wallets::get_wallet_balance,
+            wallets::get_wallet_tokens,
+            wallets::get_wallet_transactions,
             wallets::create_wallet_address,
+            wallets::list_keys,
+            wallets::update_key,
+            wallets::create_hd_wallet,
+            wallets::derive_hd_wallet_address,
             network::get_calculated_fee,
-            management::metrics
+            network::estimate_transfer_fee,
+            management::metrics,
+            management::rpc_pool_status
         ])
         .attach(prometheus_fairing.clone())
         .manage(prometheus_fairing)
-        .manage(rpc_client)
+        .manage(rpc_pool)
+        .manage(signer)
+        .manage(config)
         .ignite().await {
             Ok(rocket) => {
                 log::info!("Server started gracefully");
@@ -0,0 +1,719 @@
+use crate::{config::{Config, SignerKind}, errors::{ResponseError, Code}, models::{HdWalletInfo, KeyInfo, UpdateKeyRequest}};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce, aead::{Aead, OsRng, Payload, rand_core::RngCore}};
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use rocket::{serde::json::Json, tokio::{sync::Mutex, task}};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use solana_sdk::{derivation_path::DerivationPath, pubkey::Pubkey, signature::{Keypair, Signature, keypair_from_seed_and_derivation_path}, signer::Signer};
+use std::{collections::HashMap, path::{Path, PathBuf}, str::FromStr, sync::{Arc, RwLock}, time::{SystemTime, UNIX_EPOCH}};
+
+/// Holds the private keys, callers only ever see the key IDs and the signatures
+#[async_trait]
+pub trait SignerBackend: Send + Sync {
+    /// Public key of the key, fails when the backend does not know the key
+    async fn pubkey(&self, key_id: &str) -> Result<Pubkey, ResponseError>;
+
+    async fn sign_message(&self, key_id: &str, message: &[u8]) -> Result<Signature, ResponseError>;
+
+    /// Generates a new key inside the backend
+    async fn generate_key(&self, label: Option<String>) -> Result<KeyInfo, ResponseError>;
+
+    /// Keys of the backend, archived ones only when asked for
+    async fn list_keys(&self, _include_archived: bool) -> Result<Vec<KeyInfo>, ResponseError> {
+        Err(ResponseError::UnsupportedSignerError(Json(Code{ code: "Signer backend does not support listing the keys".to_string() })))
+    }
+
+    /// Changes the label of the key or archives it
+    async fn update_key(&self, _key_id: &str, _update: &UpdateKeyRequest) -> Result<KeyInfo, ResponseError> {
+        Err(ResponseError::UnsupportedSignerError(Json(Code{ code: "Signer backend does not support updating the keys".to_string() })))
+    }
+
+    /// Whether the keys and seeds survive a restart of the service
+    fn persists_keys(&self) -> bool {
+        false
+    }
+
+    /// Keeps the BIP39 seed of an HD wallet, importing a known seed again returns the existing wallet
+    async fn import_seed(&self, _seed: &[u8], _label: Option<String>) -> Result<HdWalletInfo, ResponseError> {
+        Err(ResponseError::UnsupportedSignerError(Json(Code{ code: "Signer backend does not support HD wallets".to_string() })))
+    }
+
+    /// Derives the key at the index, or at the next index of the wallet, and keeps it like a generated key
+    async fn derive_key(&self, _wallet_id: &str, _index: Option<u32>, _label: Option<String>) -> Result<KeyInfo, ResponseError> {
+        Err(ResponseError::UnsupportedSignerError(Json(Code{ code: "Signer backend does not support HD wallets".to_string() })))
+    }
+}
+
+pub fn from_config(config: &Config) -> Result<Arc<dyn SignerBackend>, ResponseError> {
+    let signer: Arc<dyn SignerBackend> = match config.signer {
+        SignerKind::Memory => Arc::new(InMemorySigner::default()),
+        SignerKind::Keystore => Arc::new(KeystoreSigner::new(
+            &config.keystore_path,
+            config.keystore_passphrase.clone().unwrap_or_default(),
+            config.keystore_scrypt_log_n
+        )),
+        SignerKind::Remote => Arc::new(RemoteSigner::new(&config.remote_signer_url.clone().unwrap_or_default(), config)?)
+    };
+    Ok(signer)
+}
+
+fn key_not_found(key_id: &str) -> ResponseError {
+    ResponseError::KeyNotFoundError(Json(Code{ code: format!("Key {} does not exist", key_id) }))
+}
+
+fn key_archived(key_id: &str) -> ResponseError {
+    ResponseError::InvalidSignerError(Json(Code{ code: format!("Key {} is archived", key_id) }))
+}
+
+/// Local keys are referenced by their address
+fn parse_key_id(key_id: &str) -> Result<Pubkey, ResponseError> {
+    Pubkey::from_str(key_id).map_err(|_| key_not_found(key_id))
+}
+
+fn hd_wallet_not_found(wallet_id: &str) -> ResponseError {
+    ResponseError::KeyNotFoundError(Json(Code{ code: format!("HD wallet {} does not exist", wallet_id) }))
+}
+
+/// Levels of m/44'/501'/index'/0' are hardened, the index has to stay below 2^31
+pub const MAX_DERIVATION_INDEX: u32 = (1 << 31) - 1;
+
+/// Path used by the Solana CLI and Phantom
+pub fn derivation_path(index: u32) -> String {
+    format!("m/44'/501'/{}'/0'", index)
+}
+
+/// SLIP-0010 derivation of the ed25519 key at m/44'/501'/index'/0'
+pub fn derive_keypair(seed: &[u8], index: u32) -> Result<Keypair, ResponseError> {
+    if index > MAX_DERIVATION_INDEX {
+        return Err(ResponseError::InvalidDerivationIndexError(Json(Code{ code: format!("Derivation index has to be at most {}", MAX_DERIVATION_INDEX) })));
+    }
+    keypair_from_seed_and_derivation_path(seed, Some(DerivationPath::new_bip44(Some(index), Some(0))))
+        .map_err(|err| signer_error("Failed during deriving the key", err))
+}
+
+fn hd_wallet_id(seed: &[u8]) -> Result<String, ResponseError> {
+    derive_keypair(seed, 0).map(|keypair| keypair.pubkey().to_string())
+}
+
+fn local_key_info(pubkey: &Pubkey, label: Option<String>) -> KeyInfo {
+    KeyInfo {
+        key_id: pubkey.to_string(),
+        address: pubkey.to_string(),
+        label,
+        archived: false,
+        created_at: now(),
+        hd_wallet: None,
+        derivation_index: None,
+        derivation_path: None
+    }
+}
+
+fn derived_key_info(pubkey: &Pubkey, label: Option<String>, wallet_id: &str, index: u32) -> KeyInfo {
+    KeyInfo {
+        hd_wallet: Some(wallet_id.to_string()),
+        derivation_index: Some(index),
+        derivation_path: Some(derivation_path(index)),
+        ..local_key_info(pubkey, label)
+    }
+}
+
+fn now() -> Option<u64> {
+    SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|now| now.as_secs())
+}
+
+/// Empty labels remove the label
+fn apply_update(info: &mut KeyInfo, update: &UpdateKeyRequest) {
+    if let Some(label) = &update.label {
+        info.label = Some(label.clone()).filter(|label| !label.is_empty());
+    }
+    if let Some(archived) = update.archived {
+        info.archived = archived;
+    }
+}
+
+/// Newest keys last, listings are stable between calls
+fn sort_keys(keys: &mut [KeyInfo]) {
+    keys.sort_by(|a, b| (a.created_at, &a.address).cmp(&(b.created_at, &b.address)));
+}
+
+struct MemoryKey {
+    keypair: Keypair,
+    info: KeyInfo
+}
+
+struct MemoryHdWallet {
+    seed: Vec<u8>,
+    info: HdWalletInfo
+}
+
+/// Keeps the keys in memory, they are lost when the server stops
+#[derive(Default)]
+pub struct InMemorySigner {
+    keys: RwLock<HashMap<Pubkey, MemoryKey>>,
+    hd_wallets: RwLock<HashMap<String, MemoryHdWallet>>
+}
+
+impl InMemorySigner {
+    /// Adds the keypair, its address is the key ID
+    pub fn insert(&self, keypair: Keypair, label: Option<String>) -> KeyInfo {
+        let info = local_key_info(&keypair.pubkey(), label);
+        self.insert_with_info(keypair, info)
+    }
+
+    fn insert_with_info(&self, keypair: Keypair, info: KeyInfo) -> KeyInfo {
+        self.keys.write().unwrap().insert(keypair.pubkey(), MemoryKey { keypair, info: info.clone() });
+        info
+    }
+}
+
+#[async_trait]
+impl SignerBackend for InMemorySigner {
+    async fn pubkey(&self, key_id: &str) -> Result<Pubkey, ResponseError> {
+        let pubkey = parse_key_id(key_id)?;
+        match self.keys.read().unwrap().contains_key(&pubkey) {
+            true => Ok(pubkey),
+            false => Err(key_not_found(key_id))
+        }
+    }
+
+    async fn sign_message(&self, key_id: &str, message: &[u8]) -> Result<Signature, ResponseError> {
+        let pubkey = parse_key_id(key_id)?;
+        match self.keys.read().unwrap().get(&pubkey) {
+            Some(key) if key.info.archived => Err(key_archived(key_id)),
+            Some(key) => Ok(key.keypair.sign_message(message)),
+            None => Err(key_not_found(key_id))
+        }
+    }
+
+    async fn generate_key(&self, label: Option<String>) -> Result<KeyInfo, ResponseError> {
+        Ok(self.insert(Keypair::new(), label))
+    }
+
+    async fn list_keys(&self, include_archived: bool) -> Result<Vec<KeyInfo>, ResponseError> {
+        let mut keys: Vec<KeyInfo> = self.keys.read().unwrap()
+            .values()
+            .filter(|key| include_archived || !key.info.archived)
+            .map(|key| key.info.clone())
+            .collect();
+        sort_keys(&mut keys);
+        Ok(keys)
+    }
+
+    async fn update_key(&self, key_id: &str, update: &UpdateKeyRequest) -> Result<KeyInfo, ResponseError> {
+        let pubkey = parse_key_id(key_id)?;
+        let mut keys = self.keys.write().unwrap();
+        let key = keys.get_mut(&pubkey).ok_or_else(|| key_not_found(key_id))?;
+        apply_update(&mut key.info, update);
+        Ok(key.info.clone())
+    }
+
+    async fn import_seed(&self, seed: &[u8], label: Option<String>) -> Result<HdWalletInfo, ResponseError> {
+        let wallet_id = hd_wallet_id(seed)?;
+        let mut hd_wallets = self.hd_wallets.write().unwrap();
+        let wallet = hd_wallets.entry(wallet_id.clone()).or_insert_with(|| MemoryHdWallet {
+            seed: seed.to_vec(),
+            info: HdWalletInfo { wallet_id, label, next_index: 0, mnemonic: None }
+        });
+        Ok(wallet.info.clone())
+    }
+
+    async fn derive_key(&self, wallet_id: &str, index: Option<u32>, label: Option<String>) -> Result<KeyInfo, ResponseError> {
+        let (keypair, index) = {
+            let mut hd_wallets = self.hd_wallets.write().unwrap();
+            let wallet = hd_wallets.get_mut(wallet_id).ok_or_else(|| hd_wallet_not_found(wallet_id))?;
+            let index = index.unwrap_or(wallet.info.next_index);
+            let keypair = derive_keypair(&wallet.seed, index)?;
+            wallet.info.next_index = wallet.info.next_index.max(index.saturating_add(1));
+            (keypair, index)
+        };
+
+        // Deriving an index again returns the key kept the first time
+        if let Some(key) = self.keys.read().unwrap().get(&keypair.pubkey()) {
+            return Ok(key.info.clone());
+        }
+        let info = derived_key_info(&keypair.pubkey(), label, wallet_id, index);
+        Ok(self.insert_with_info(keypair, info))
+    }
+}
+
+const KEYSTORE_KDF: &str = "scrypt";
+const KEYSTORE_CIPHER: &str = "aes-256-gcm";
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Content of a key file of the keystore, the keypair is encrypted with a key derived from the passphrase
+#[derive(Debug, Serialize, Deserialize)]
+struct KeystoreFile {
+    address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default, rename="createdAt", skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+    #[serde(default, rename="hdWallet", skip_serializing_if = "Option::is_none")]
+    hd_wallet: Option<String>,
+    #[serde(default, rename="derivationIndex", skip_serializing_if = "Option::is_none")]
+    derivation_index: Option<u32>,
+    crypto: KeystoreCrypto
+}
+
+impl KeystoreFile {
+    fn info(&self) -> KeyInfo {
+        KeyInfo {
+            key_id: self.address.clone(),
+            address: self.address.clone(),
+            label: self.label.clone(),
+            archived: self.archived,
+            created_at: self.created_at,
+            hd_wallet: self.hd_wallet.clone(),
+            derivation_index: self.derivation_index,
+            derivation_path: self.derivation_index.map(derivation_path)
+        }
+    }
+}
+
+/// HD wallet of the keystore, the seed is encrypted like the keypairs
+#[derive(Debug, Serialize, Deserialize)]
+struct HdWalletFile {
+    #[serde(rename="walletId")]
+    wallet_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(rename="nextIndex")]
+    next_index: u32,
+    #[serde(default, rename="createdAt", skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+    crypto: KeystoreCrypto
+}
+
+impl HdWalletFile {
+    fn info(&self) -> HdWalletInfo {
+        HdWalletInfo {
+            wallet_id: self.wallet_id.clone(),
+            label: self.label.clone(),
+            next_index: self.next_index,
+            mnemonic: None
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct KeystoreCrypto {
+    kdf: String,
+    #[serde(rename="kdfParams")]
+    kdf_params: ScryptParams,
+    cipher: String,
+    nonce: String,
+    ciphertext: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ScryptParams {
+    #[serde(rename="logN")]
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: String
+}
+
+fn signer_error(message: &str, err: impl std::fmt::Display) -> ResponseError {
+    log::error!("{}: {}", message, err);
+    ResponseError::SignerError(Json(Code{ code: message.to_string() }))
+}
+
+fn derive_key(passphrase: &str, params: &ScryptParams, salt: &[u8]) -> Result<[u8; 32], ResponseError> {
+    let scrypt_params = scrypt::Params::new(params.log_n, params.r, params.p, 32)
+        .map_err(|err| signer_error("Invalid scrypt parameters of the key file", err))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &scrypt_params, &mut key)
+        .map_err(|err| signer_error("Failed during deriving the key of the keystore", err))?;
+    Ok(key)
+}
+
+/// The associated data (the address or the wallet ID) is authenticated too, a file cannot be passed off as another one
+fn encrypt_secret(secret: &[u8], associated_data: &str, passphrase: &str, log_n: u8) -> Result<KeystoreCrypto, ResponseError> {
+    let mut salt = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let kdf_params = ScryptParams { log_n, r: SCRYPT_R, p: SCRYPT_P, salt: BASE64.encode(salt) };
+    let key = derive_key(passphrase, &kdf_params, &salt)?;
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|err| signer_error("Failed during creating the cipher of the keystore", err))?;
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), Payload { msg: secret, aad: associated_data.as_bytes() })
+        .map_err(|err| signer_error("Failed during encrypting the key", err))?;
+
+    Ok(KeystoreCrypto {
+        kdf: KEYSTORE_KDF.to_string(),
+        kdf_params,
+        cipher: KEYSTORE_CIPHER.to_string(),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext)
+    })
+}
+
+fn decrypt_secret(crypto: &KeystoreCrypto, associated_data: &str, passphrase: &str) -> Result<Vec<u8>, ResponseError> {
+    if crypto.kdf != KEYSTORE_KDF || crypto.cipher != KEYSTORE_CIPHER {
+        return Err(signer_error("Unsupported encryption of the key file", &crypto.cipher));
+    }
+
+    let salt = BASE64.decode(&crypto.kdf_params.salt)
+        .map_err(|err| signer_error("Invalid salt in the key file", err))?;
+    let nonce = BASE64.decode(&crypto.nonce)
+        .map_err(|err| signer_error("Invalid nonce in the key file", err))?;
+    let ciphertext = BASE64.decode(&crypto.ciphertext)
+        .map_err(|err| signer_error("Invalid ciphertext in the key file", err))?;
+    if nonce.len() != 12 {
+        return Err(signer_error("Invalid nonce in the key file", nonce.len()));
+    }
+
+    let key = derive_key(passphrase, &crypto.kdf_params, &salt)?;
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|err| signer_error("Failed during creating the cipher of the keystore", err))?;
+    cipher.decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: associated_data.as_bytes() })
+        .map_err(|err| signer_error("Failed during decrypting the key, the passphrase may be wrong", err))
+}
+
+fn encrypt_keypair(keypair: &Keypair, info: &KeyInfo, passphrase: &str, log_n: u8) -> Result<KeystoreFile, ResponseError> {
+    Ok(KeystoreFile {
+        address: info.address.clone(),
+        label: info.label.clone(),
+        archived: info.archived,
+        created_at: info.created_at,
+        hd_wallet: info.hd_wallet.clone(),
+        derivation_index: info.derivation_index,
+        crypto: encrypt_secret(&keypair.to_bytes(), &info.address, passphrase, log_n)?
+    })
+}
+
+fn decrypt_keypair(file: &KeystoreFile, passphrase: &str) -> Result<Keypair, ResponseError> {
+    let bytes = decrypt_secret(&file.crypto, &file.address, passphrase)?;
+    let keypair = Keypair::from_bytes(&bytes)
+        .map_err(|err| signer_error("Invalid keypair in the key file", err))?;
+    if keypair.pubkey().to_string() != file.address {
+        return Err(signer_error("Keypair of the key file does not match its address", &file.address));
+    }
+    Ok(keypair)
+}
+
+fn read_json_file<T: DeserializeOwned>(path: &Path, not_found: impl FnOnce() -> ResponseError) -> Result<T, ResponseError> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Err(not_found()),
+        Err(err) => return Err(signer_error("Failed during reading the key file", err))
+    };
+    serde_json::from_slice(&content)
+        .map_err(|err| signer_error("Invalid key file", err))
+}
+
+fn read_key_file(key_path: &Path, key_id: &str) -> Result<KeystoreFile, ResponseError> {
+    read_json_file(key_path, || key_not_found(key_id))
+}
+
+/// Written next to the target and renamed, a crash cannot leave a truncated key file behind
+fn write_json_file<T: Serialize>(path: &Path, file: &T) -> Result<(), ResponseError> {
+    let content = serde_json::to_vec_pretty(file)
+        .map_err(|err| signer_error("Failed during serializing the key file", err))?;
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)
+            .map_err(|err| signer_error("Failed during creating the keystore directory", err))?;
+    }
+    let temporary_path = path.with_extension("json.tmp");
+    std::fs::write(&temporary_path, content)
+        .map_err(|err| signer_error("Failed during writing the key file", err))?;
+    std::fs::rename(&temporary_path, path)
+        .map_err(|err| signer_error("Failed during writing the key file", err))
+}
+
+/// Key files are read and decrypted outside of the async workers, scrypt is CPU bound
+async fn run_blocking<T: Send + 'static>(operation: impl FnOnce() -> Result<T, ResponseError> + Send + 'static) -> Result<T, ResponseError> {
+    task::spawn_blocking(operation).await
+        .map_err(|err| signer_error("Keystore operation failed", err))?
+}
+
+/// Keeps every key encrypted in its own file, named after the address of the key
+pub struct KeystoreSigner {
+    path: PathBuf,
+    passphrase: String,
+    scrypt_log_n: u8,
+    /// Deriving the next index reads and updates the wallet file, concurrent derivations would get the same index
+    hd_lock: Mutex<()>
+}
+
+impl KeystoreSigner {
+    pub fn new(path: impl AsRef<Path>, passphrase: String, scrypt_log_n: u8) -> Self {
+        Self { path: path.as_ref().to_path_buf(), passphrase, scrypt_log_n, hd_lock: Mutex::new(()) }
+    }
+
+    /// HD wallets are kept apart from the keys, in `hd/<walletId>.json`
+    fn hd_wallet_path(&self, wallet_id: &str) -> Result<PathBuf, ResponseError> {
+        let wallet_address = Pubkey::from_str(wallet_id).map_err(|_| hd_wallet_not_found(wallet_id))?;
+        Ok(self.path.join("hd").join(format!("{}.json", wallet_address)))
+    }
+
+    fn key_path(&self, key_id: &str) -> Result<PathBuf, ResponseError> {
+        // Parsing the address also keeps the key ID from escaping the keystore directory
+        let pubkey = parse_key_id(key_id)?;
+        Ok(self.path.join(format!("{}.json", pubkey)))
+    }
+
+    async fn read(&self, key_id: &str) -> Result<KeystoreFile, ResponseError> {
+        let key_path = self.key_path(key_id)?;
+        let key_id = key_id.to_string();
+        run_blocking(move || read_key_file(&key_path, &key_id)).await
+    }
+}
+
+#[async_trait]
+impl SignerBackend for KeystoreSigner {
+    fn persists_keys(&self) -> bool {
+        true
+    }
+
+    async fn pubkey(&self, key_id: &str) -> Result<Pubkey, ResponseError> {
+        self.read(key_id).await?;
+        parse_key_id(key_id)
+    }
+
+    async fn sign_message(&self, key_id: &str, message: &[u8]) -> Result<Signature, ResponseError> {
+        let file = self.read(key_id).await?;
+        if file.archived {
+            return Err(key_archived(key_id));
+        }
+
+        let passphrase = self.passphrase.clone();
+        let keypair = run_blocking(move || decrypt_keypair(&file, &passphrase)).await?;
+        Ok(keypair.sign_message(message))
+    }
+
+    async fn generate_key(&self, label: Option<String>) -> Result<KeyInfo, ResponseError> {
+        let keypair = Keypair::new();
+        let key_path = self.key_path(&keypair.pubkey().to_string())?;
+        let passphrase = self.passphrase.clone();
+        let scrypt_log_n = self.scrypt_log_n;
+
+        run_blocking(move || {
+            let file = encrypt_keypair(&keypair, &local_key_info(&keypair.pubkey(), label), &passphrase, scrypt_log_n)?;
+            write_json_file(&key_path, &file)?;
+            Ok(file.info())
+        }).await
+    }
+
+    async fn list_keys(&self, include_archived: bool) -> Result<Vec<KeyInfo>, ResponseError> {
+        let path = self.path.clone();
+
+        let mut keys = run_blocking(move || {
+            let entries = match std::fs::read_dir(&path) {
+                Ok(entries) => entries,
+                // Nothing was generated yet
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(err) => return Err(signer_error("Failed during reading the keystore directory", err))
+            };
+
+            let mut keys = Vec::new();
+            for entry in entries {
+                let key_path = entry
+                    .map_err(|err| signer_error("Failed during reading the keystore directory", err))?
+                    .path();
+                if key_path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                    continue;
+                }
+                match read_key_file(&key_path, &key_path.to_string_lossy()) {
+                    Ok(file) => keys.push(file.info()),
+                    Err(_) => log::warn!("Skipping the invalid key file {}", key_path.display())
+                }
+            }
+            Ok(keys)
+        }).await?;
+
+        keys.retain(|key| include_archived || !key.archived);
+        sort_keys(&mut keys);
+        Ok(keys)
+    }
+
+    async fn update_key(&self, key_id: &str, update: &UpdateKeyRequest) -> Result<KeyInfo, ResponseError> {
+        let key_path = self.key_path(key_id)?;
+        let key_id = key_id.to_string();
+        let label = update.label.clone();
+        let archived = update.archived;
+
+        run_blocking(move || {
+            let mut file = read_key_file(&key_path, &key_id)?;
+            let mut info = file.info();
+            apply_update(&mut info, &UpdateKeyRequest { label, archived });
+            // Only the address is authenticated with the keypair, the metadata can change without decrypting
+            file.label = info.label.clone();
+            file.archived = info.archived;
+            write_json_file(&key_path, &file)?;
+            Ok(info)
+        }).await
+    }
+
+    async fn import_seed(&self, seed: &[u8], label: Option<String>) -> Result<HdWalletInfo, ResponseError> {
+        let wallet_id = hd_wallet_id(seed)?;
+        let wallet_path = self.hd_wallet_path(&wallet_id)?;
+        let seed = seed.to_vec();
+        let passphrase = self.passphrase.clone();
+        let scrypt_log_n = self.scrypt_log_n;
+
+        let _guard = self.hd_lock.lock().await;
+        run_blocking(move || {
+            if wallet_path.exists() {
+                return read_json_file::<HdWalletFile>(&wallet_path, || hd_wallet_not_found(&wallet_id)).map(|file| file.info());
+            }
+            let file = HdWalletFile {
+                crypto: encrypt_secret(&seed, &wallet_id, &passphrase, scrypt_log_n)?,
+                wallet_id,
+                label,
+                next_index: 0,
+                created_at: now()
+            };
+            write_json_file(&wallet_path, &file)?;
+            Ok(file.info())
+        }).await
+    }
+
+    async fn derive_key(&self, wallet_id: &str, index: Option<u32>, label: Option<String>) -> Result<KeyInfo, ResponseError> {
+        let wallet_path = self.hd_wallet_path(wallet_id)?;
+        let keys_path = self.path.clone();
+        let wallet_id = wallet_id.to_string();
+        let passphrase = self.passphrase.clone();
+        let scrypt_log_n = self.scrypt_log_n;
+
+        let _guard = self.hd_lock.lock().await;
+        run_blocking(move || {
+            let mut wallet: HdWalletFile = read_json_file(&wallet_path, || hd_wallet_not_found(&wallet_id))?;
+            let seed = decrypt_secret(&wallet.crypto, &wallet.wallet_id, &passphrase)?;
+            let index = index.unwrap_or(wallet.next_index);
+            let keypair = derive_keypair(&seed, index)?;
+
+            // Deriving an index again returns the key kept the first time
+            let key_path = keys_path.join(format!("{}.json", keypair.pubkey()));
+            let info = if key_path.exists() {
+                read_key_file(&key_path, &keypair.pubkey().to_string())?.info()
+            } else {
+                let info = derived_key_info(&keypair.pubkey(), label, &wallet_id, index);
+                write_json_file(&key_path, &encrypt_keypair(&keypair, &info, &passphrase, scrypt_log_n)?)?;
+                info
+            };
+
+            if index >= wallet.next_index {
+                wallet.next_index = index.saturating_add(1);
+                write_json_file(&wallet_path, &wallet)?;
+            }
+            Ok(info)
+        }).await
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteKey {
+    #[serde(rename="keyId")]
+    key_id: String,
+    #[serde(rename="publicKey")]
+    public_key: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteKeyRequest {
+    label: Option<String>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteSignRequest {
+    /// Base64 encoded message
+    message: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteSignResponse {
+    signature: String
+}
+
+/// Delegates to a signing service over HTTP:
+/// `GET /keys/<keyId>`, `POST /keys/<keyId>/sign` and `POST /keys`
+pub struct RemoteSigner {
+    url: reqwest::Url,
+    client: reqwest::Client
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, config: &Config) -> Result<Self, ResponseError> {
+        let url = reqwest::Url::parse(url)
+            .map_err(|err| {
+                log::error!("Error while parsing the remote signer URL: {}", err);
+                ResponseError::ConfigError(Json(Code{ code: "Invalid remote_signer_url".to_string() }))
+            })?;
+        if url.cannot_be_a_base() {
+            return Err(ResponseError::ConfigError(Json(Code{ code: "Invalid remote_signer_url".to_string() })));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout())
+            .build()
+            .map_err(|err| {
+                log::error!("Error while creating the remote signer client: {}", err);
+                ResponseError::ConfigError(Json(Code{ code: "Failed during creating the remote signer client".to_string() }))
+            })?;
+        Ok(Self { url, client })
+    }
+
+    fn endpoint(&self, segments: &[&str]) -> reqwest::Url {
+        let mut url = self.url.clone();
+        // Checked in new, key IDs are percent-encoded as path segments
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        url
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(&self, request: reqwest::RequestBuilder, key_id: Option<&str>) -> Result<T, ResponseError> {
+        let response = request.send().await
+            .map_err(|err| signer_error("Failed during calling the remote signer", err))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            if let Some(key_id) = key_id {
+                return Err(key_not_found(key_id));
+            }
+        }
+        response.error_for_status()
+            .map_err(|err| signer_error("Remote signer rejected the request", err))?
+            .json::<T>().await
+            .map_err(|err| signer_error("Invalid response of the remote signer", err))
+    }
+}
+
+fn parse_remote_pubkey(key: &RemoteKey) -> Result<Pubkey, ResponseError> {
+    Pubkey::from_str(&key.public_key)
+        .map_err(|err| signer_error("Invalid public key returned by the remote signer", err))
+}
+
+#[async_trait]
+impl SignerBackend for RemoteSigner {
+    fn persists_keys(&self) -> bool {
+        true
+    }
+
+    async fn pubkey(&self, key_id: &str) -> Result<Pubkey, ResponseError> {
+        let key: RemoteKey = self.send(self.client.get(self.endpoint(&["keys", key_id])), Some(key_id)).await?;
+        parse_remote_pubkey(&key)
+    }
+
+    async fn sign_message(&self, key_id: &str, message: &[u8]) -> Result<Signature, ResponseError> {
+        let request = RemoteSignRequest { message: BASE64.encode(message) };
+        let response: RemoteSignResponse = self.send(self.client.post(self.endpoint(&["keys", key_id, "sign"])).json(&request), Some(key_id)).await?;
+        Signature::from_str(&response.signature)
+            .map_err(|err| signer_error("Invalid signature returned by the remote signer", err))
+    }
+
+    async fn generate_key(&self, label: Option<String>) -> Result<KeyInfo, ResponseError> {
+        let request = RemoteKeyRequest { label: label.clone() };
+        let key: RemoteKey = self.send(self.client.post(self.endpoint(&["keys"])).json(&request), None).await?;
+        let pubkey = parse_remote_pubkey(&key)?;
+        Ok(KeyInfo {
+            key_id: key.key_id,
+            created_at: None,
+            ..local_key_info(&pubkey, label)
+        })
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{config::Commitment, errors::{ResponseError, Code}, models::Amount, pool::RpcPool};
+use rocket::serde::json::Json;
+use solana_program::{instruction::Instruction, program_pack::Pack};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::time::{SystemTime, UNIX_EPOCH};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions, interest_bearing_mint::InterestBearingConfig, transfer_fee::{TransferFeeConfig, instruction::transfer_checked_with_fee}},
+    state::{Account as TokenAccount, Mint}
+};
+
+/// Mint of the SPL Token or Token-2022 program, with the extensions affecting transfers
+#[derive(Debug, Clone)]
+pub struct MintInfo {
+    pub address: Pubkey,
+    /// Token program owning the mint and its token accounts
+    pub program_id: Pubkey,
+    pub decimals: u8,
+    pub transfer_fee_config: Option<TransferFeeConfig>,
+    /// Interest accrued by the token accounts, only shown in their UI amount
+    pub interest_bearing_config: Option<InterestBearingConfig>,
+    /// Extensions of Token-2022 mints, some of them require extensions on the token accounts
+    pub extension_types: Vec<ExtensionType>
+}
+
+impl MintInfo {
+    pub fn is_token_2022(&self) -> bool {
+        self.program_id == spl_token_2022::id()
+    }
+
+    /// Fee withheld from a transfer of the given amount during the epoch, in base units of the mint
+    pub fn transfer_fee(&self, epoch: u64, amount: u64) -> Option<u64> {
+        match &self.transfer_fee_config {
+            Some(transfer_fee_config) => transfer_fee_config.calculate_epoch_fee(epoch, amount),
+            None => Some(0)
+        }
+    }
+
+    /// Amount of the mint, the UI amount of interest-bearing mints includes the interest accrued until now
+    pub fn amount(&self, amount: u64) -> Amount {
+        let unix_timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        let ui_amount_string = self.interest_bearing_config
+            .and_then(|interest_bearing_config| interest_bearing_config.amount_to_ui_amount(amount, self.decimals, unix_timestamp));
+
+        match ui_amount_string {
+            Some(ui_amount_string) => Amount::new(amount, self.decimals).with_ui_amount_string(ui_amount_string),
+            None => Amount::new(amount, self.decimals)
+        }
+    }
+
+    /// Size of the associated token accounts of the mint, the rent exemption of new accounts depends on it
+    pub fn associated_token_account_len(&self) -> usize {
+        if !self.is_token_2022() {
+            return TokenAccount::LEN;
+        }
+
+        // The associated token account program makes the owner of Token-2022 accounts immutable
+        let mut account_extension_types = ExtensionType::get_required_init_account_extensions(&self.extension_types);
+        account_extension_types.push(ExtensionType::ImmutableOwner);
+        ExtensionType::get_account_len::<TokenAccount>(&account_extension_types)
+    }
+
+    /// transferChecked, or transferCheckedWithFee for mints with a transfer fee, between two token accounts of the mint
+    pub fn transfer_checked(&self, source: &Pubkey, destination: &Pubkey, authority: &Pubkey, amount: u64, epoch: u64) -> Result<Instruction, ResponseError> {
+        let instruction = match &self.transfer_fee_config {
+            Some(_) => {
+                let fee = self.transfer_fee(epoch, amount)
+                    .ok_or_else(|| ResponseError::CreateTransferError(Json(Code{ code: "Failed during calculating the transfer fee".to_string() })))?;
+                transfer_checked_with_fee(&self.program_id, source, &self.address, destination, authority, &[], amount, self.decimals, fee)
+            },
+            None => spl_token_2022::instruction::transfer_checked(&self.program_id, source, &self.address, destination, authority, &[], amount, self.decimals)
+        };
+
+        instruction.map_err(|err| {
+            log::error!("Error during creating the transaction instruction: {}", err);
+            ResponseError::CreateTransferError(Json(Code{ code: "Failed during creating the transaction instruction".to_string() }))
+        })
+    }
+}
+
+/// Reads the mint account, the owner of the account tells which token program the mint belongs to
+pub async fn get_mint(rpc_pool: &RpcPool, mint: &Pubkey, commitment: Commitment) -> Result<MintInfo, ResponseError> {
+    let account = rpc_pool.call(|rpc_client| rpc_client.get_account_with_commitment(mint, commitment.to_commitment_config())).await
+        .map(|response| response.value)
+        .map_err(|err| {
+            log::error!("Failed during getting the mint: {}", err);
+            ResponseError::GetMintError(Json(Code{ code: "Failed during getting the mint".to_string() }))
+        })?
+        .ok_or_else(|| ResponseError::GetMintError(Json(Code{ code: format!("Mint {} does not exist", mint) })))?;
+
+    if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+        return Err(ResponseError::GetMintError(Json(Code{ code: format!("{} is not a mint of a token program", mint) })));
+    }
+
+    // Mints of the SPL Token program are Token-2022 mints without extensions
+    let state = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|err| {
+            log::error!("Error while unpacking the mint {}: {}", mint, err);
+            ResponseError::GetMintError(Json(Code{ code: format!("{} is not a mint of a token program", mint) }))
+        })?;
+
+    Ok(MintInfo {
+        address: *mint,
+        program_id: account.owner,
+        decimals: state.base.decimals,
+        transfer_fee_config: state.get_extension::<TransferFeeConfig>().ok().copied(),
+        interest_bearing_config: state.get_extension::<InterestBearingConfig>().ok().copied(),
+        extension_types: state.get_extension_types().unwrap_or_default()
+    })
+}
+
+/// Base state of an account of the SPL Token or Token-2022 program, None for the other accounts
+pub fn unpack_token_account(account: &Account) -> Option<TokenAccount> {
+    if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+        return None;
+    }
+
+    // Mints fail to unpack as token accounts, either by their size or by their account type
+    StateWithExtensions::<TokenAccount>::unpack(&account.data)
+        .ok()
+        .map(|state| state.base)
+}
+
+/// Current epoch, transfer fees of Token-2022 mints depend on it
+pub async fn get_epoch(rpc_pool: &RpcPool, commitment: Commitment) -> Result<u64, ResponseError> {
+    rpc_pool.call(|rpc_client| rpc_client.get_epoch_info_with_commitment(commitment.to_commitment_config())).await
+        .map(|epoch_info| epoch_info.epoch)
+        .map_err(|err| {
+            log::error!("Failed during getting the epoch: {}", err);
+            ResponseError::GetEpochError(Json(Code{ code: "Failed during getting the epoch".to_string() }))
+        })
+}
+
+pub async fn account_exists(rpc_pool: &RpcPool, address: &Pubkey, commitment: Commitment) -> Result<bool, ResponseError> {
+    rpc_pool.call(|rpc_client| rpc_client.get_account_with_commitment(address, commitment.to_commitment_config())).await
+        .map(|response| response.value.is_some())
+        .map_err(|err| {
+            log::error!("Failed during getting the account: {}", err);
+            ResponseError::GetAccountError(Json(Code{ code: "Failed during getting the account".to_string() }))
+        })
+}
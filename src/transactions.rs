@@ -1,144 +1,425 @@
-use std::{sync::Arc, str::FromStr};
+use std::{collections::HashMap, sync::Arc, str::FromStr, time::{Duration, Instant}};
 
 use rust_base58::FromBase58;
-use rocket::{State, serde::json::Json};
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcBlockConfig};
-use solana_sdk::{signature::Signature, transaction::Transaction, pubkey::Pubkey, signature::Keypair, commitment_config::CommitmentConfig};
-use solana_transaction_status::{UiTransactionEncoding, TransactionDetails};
-use spl_token::instruction::transfer;
+use rocket::{State, serde::json::Json, tokio::time::sleep};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{client_error::ClientErrorKind, rpc_request::{RpcError, RpcResponseErrorData}};
+use solana_client::rpc_config::{RpcBlockConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig};
+use num_traits::FromPrimitive;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use solana_sdk::{account::Account, hash::Hash, instruction::InstructionError, message::Message, system_instruction::SystemError, transaction::TransactionError, sanitize::Sanitize, signature::Signature, transaction::Transaction, pubkey::Pubkey, signature::Keypair, signer::Signer};
+use solana_transaction_status::{UiTransactionEncoding, TransactionDetails, TransactionStatus as SignatureStatus};
+use spl_associated_token_account::{error::AssociatedTokenAccountError, get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent};
 use solana_program::instruction::Instruction;
-use crate::{errors::{ResponseError, Code}, models::{TransactionInfoConvertiable, SendTransactionRequest, SendTransactionResponse, SignTransactionRequest, SignTransactionResponse, TransactionInfo, ConfirmationCount}};
+use crate::{config::{Commitment, Config, SOL_PRECISION}, errors::{ResponseError, Code}, fees::{check_compute_budget, compute_budget_instructions}, models::{AccountInfo, Amount, ApiVersion, BlockReference, BuildTransactionRequest, BuildTransactionResponse, CombineTransactionRequest, ComputeBudget, TransactionInfoConvertiable, SendTransactionRequest, SendTransactionResponse, SignTransactionRequest, SignTransactionResponse, SimulateTransactionRequest, SimulationResult, TransactionInfo, TokenBalanceOwner, TransactionStatus, ConfirmationCount, push_balance_change}, pool::RpcPool, queries::SendTransactionQuery, signers::SignerBackend, tokens::{account_exists, get_epoch, get_mint, unpack_token_account}};
+
+/// Interval between two status requests while waiting for a sent transaction
+const SIGNATURE_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 #[post("/transactions/sign", data = "<transaction_parameters>")]
-pub fn sign_transaction(
+pub async fn sign_transaction(
     transaction_parameters: Json<SignTransactionRequest>,
-    rpc_client: &State<Arc<RpcClient>>
+    rpc_pool: &State<Arc<RpcPool>>,
+    signer: &State<Arc<dyn SignerBackend>>,
+    config: &State<Config>
 ) -> Result<Json<SignTransactionResponse>, ResponseError> {
-    
-    if transaction_parameters.from.is_empty(){
+    let accounts = parse_transfer_accounts(&transaction_parameters.from, &transaction_parameters.to, transaction_parameters.fee_payer.as_deref())?;
+    let first_sender = accounts.senders[0];
+
+    // The private key of the request is the key of the first sender, the other signers are always in the backend
+    let keypair = match (&transaction_parameters.key_id, &transaction_parameters.private_key) {
+        (_, None) => None,
+        (None, Some(privkey)) if config.insecure_private_keys => {
+            let mut bytes_of_privatekey = privkey.from_base58().map_err(|err|{
+                log::error!("Error during creating the byte array of private key: {}", err);
+                ResponseError::CreateByteArrayError(Json(Code{code: "Failed during creating the byte array of private key".to_string()}))
+            })?;
+
+            let mut bytes_of_publickey = transaction_parameters.from[0].adress.from_base58().map_err(|err|{
+                log::error!("Error during creating the byte array of public key: {}", err);
+                ResponseError::CreateByteArrayError(Json(Code{code: "Failed during creating the byte array of public key".to_string()}))
+            })?;
+
+            bytes_of_privatekey.append(& mut bytes_of_publickey);
+
+            let keypair: Keypair = Keypair::from_bytes(&bytes_of_privatekey)
+            .map_err(|err|{
+                log::error!("Error during creating the keypair object: {}", err);
+                ResponseError::CreateKeypairError(Json(Code{code: "Failed during creating the keypair object".to_string()}))
+            })?;
+            Some(keypair)
+        },
+        (None, Some(_)) => {
+            return Err(ResponseError::InsecureModeError(Json(Code{code: "Private keys are only accepted in insecure mode, reference the key with keyId".to_string()})));
+        },
+        (Some(_), Some(_)) => {
+            return Err(ResponseError::InvalidSignerError(Json(Code{code: "Only one of keyId and private_key can be given".to_string()})));
+        }
+    };
+
+    let (message, _) = build_transfer_message(&accounts, &transaction_parameters.to, transaction_parameters.create_associated_token_account, &transaction_parameters.compute_budget, rpc_pool, config).await?;
+    let message_data = message.serialize();
+    let mut signatures: Vec<Signature> = Vec::with_capacity(message.header.num_required_signatures as usize);
+    let mut missing_signatures: Vec<String> = Vec::new();
+    for signer_address in message.signer_keys() {
+        let signature = match &keypair {
+            Some(keypair) if *signer_address == first_sender => Some(keypair.sign_message(&message_data)),
+            _ => {
+                // Local backends know their keys by address, signers can be signed for without a key ID
+                let key_id = transaction_parameters.key_ids.get(&signer_address.to_string()).cloned()
+                    .or_else(|| transaction_parameters.key_id.clone().filter(|_| *signer_address == first_sender))
+                    .unwrap_or_else(|| signer_address.to_string());
+                sign_with_backend(signer.inner().as_ref(), &key_id, signer_address, &message_data, transaction_parameters.partial).await?
+            }
+        };
+
+        match signature {
+            Some(signature) => {
+                // A remote backend or a wrong private key would otherwise produce a transaction rejected by the cluster
+                if !signature.verify(signer_address.as_ref(), &message_data) {
+                    log::error!("Signature does not match the signer {}", signer_address);
+                    return Err(ResponseError::SignerError(Json(Code{ code: format!("Signature does not match the signer {}", signer_address) })));
+                }
+                signatures.push(signature);
+            },
+            None => {
+                signatures.push(Signature::default());
+                missing_signatures.push(signer_address.to_string());
+            }
+        }
+    }
+
+    signed_transaction_response(Transaction { signatures, message }, missing_signatures).map(Json)
+}
+
+/// Signs with the key of the backend, None when the backend does not know the key and partial signing is allowed
+async fn sign_with_backend(
+    signer: &dyn SignerBackend,
+    key_id: &str,
+    signer_address: &Pubkey,
+    message_data: &[u8],
+    partial: bool
+) -> Result<Option<Signature>, ResponseError> {
+    let pubkey = match signer.pubkey(key_id).await {
+        Ok(pubkey) => pubkey,
+        Err(ResponseError::KeyNotFoundError(_)) if partial => return Ok(None),
+        Err(err) => return Err(err)
+    };
+    if pubkey != *signer_address {
+        return Err(ResponseError::InvalidSignerError(Json(Code{code: format!("Key {} does not belong to {}", key_id, signer_address)})));
+    }
+
+    signer.sign_message(key_id, message_data).await.map(Some)
+}
+
+/// Builds the same transaction as the sign endpoint without signing it, for signers outside of this service
+#[post("/transactions/build", data = "<transaction_parameters>")]
+pub async fn build_transaction(
+    transaction_parameters: Json<BuildTransactionRequest>,
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>
+) -> Result<Json<BuildTransactionResponse>, ResponseError> {
+    let accounts = parse_transfer_accounts(&transaction_parameters.from, &transaction_parameters.to, transaction_parameters.fee_payer.as_deref())?;
+    let (message, last_valid_block_height) = build_transfer_message(&accounts, &transaction_parameters.to, transaction_parameters.create_associated_token_account, &transaction_parameters.compute_budget, rpc_pool, config).await?;
+
+    Ok(Json(BuildTransactionResponse {
+        required_signers: message.signer_keys().iter().map(|signer| signer.to_string()).collect(),
+        recent_blockhash: message.recent_blockhash.to_string(),
+        last_valid_block_height,
+        message: BASE64.encode(message.serialize())
+    }))
+}
+
+/// Attaches the signatures produced for a built or partially signed transaction, every required signer has to have signed unless partial
+#[post("/transactions/combine", data = "<transaction_parameters>")]
+pub async fn combine_transaction(
+    transaction_parameters: Json<CombineTransactionRequest>
+) -> Result<Json<SignTransactionResponse>, ResponseError> {
+    let (message, mut signatures) = match (&transaction_parameters.message, &transaction_parameters.signed_transaction) {
+        (Some(message), None) => {
+            let message = decode_message(message)?;
+            let signatures = vec![None; message.header.num_required_signatures as usize];
+            (message, signatures)
+        },
+        (None, Some(signed_transaction)) => decode_partially_signed_transaction(signed_transaction)?,
+        _ => {
+            return Err(ResponseError::InvalidMessageError(Json(Code{ code: "Exactly one of message and signedTransaction has to be given".to_string() })));
+        }
+    };
+    let message_data = message.serialize();
+    let signers: Vec<Pubkey> = message.signer_keys().into_iter().copied().collect();
+
+    for external_signature in transaction_parameters.signatures.iter() {
+        let public_key = Pubkey::from_str(&external_signature.public_key)
+            .map_err(|err| {
+                log::error!("Error during creating the Pubkey object from the signer address: {}", err);
+                ResponseError::CreatePubkeyError(Json(Code{ code: "Failed during creating the Pubkey object from the signer address".to_string() }))
+            })?;
+        let signature = Signature::from_str(&external_signature.signature)
+            .map_err(|err| {
+                log::error!("Failed during parsing the signature of {}: {}", public_key, err);
+                ResponseError::InvalidSignatureError(Json(Code{ code: format!("Signature of {} is not a valid signature", public_key) }))
+            })?;
+
+        let position = signers.iter().position(|signer| *signer == public_key)
+            .ok_or_else(|| ResponseError::InvalidSignatureError(Json(Code{ code: format!("{} is not a signer of the message", public_key) })))?;
+        if !signature.verify(public_key.as_ref(), &message_data) {
+            return Err(ResponseError::InvalidSignatureError(Json(Code{ code: format!("Signature of {} does not match the message", public_key) })));
+        }
+        signatures[position] = Some(signature);
+    }
+
+    let missing_signers: Vec<String> = signers.iter().zip(signatures.iter())
+        .filter(|(_, signature)| signature.is_none())
+        .map(|(signer, _)| signer.to_string())
+        .collect();
+    if !missing_signers.is_empty() && !transaction_parameters.partial {
+        return Err(ResponseError::InvalidSignatureError(Json(Code{ code: format!("Missing signatures of {}", missing_signers.join(", ")) })));
+    }
+
+    let signatures = signatures.into_iter().map(Option::unwrap_or_default).collect();
+    signed_transaction_response(Transaction { signatures, message }, missing_signers).map(Json)
+}
+
+/// Fee payer and sender of each recipient of the transfers
+pub struct TransferAccounts {
+    pub fee_payer: Pubkey,
+    /// One per recipient, in the order of the recipients
+    pub senders: Vec<Pubkey>
+}
+
+/// A single sender pays every recipient, several senders are paired with the recipients in order.
+/// The first sender pays the fees unless another fee payer is given
+pub fn parse_transfer_accounts(from: &[AccountInfo], to: &[AccountInfo], fee_payer: Option<&str>) -> Result<TransferAccounts, ResponseError> {
+    if from.is_empty(){
         return Err(ResponseError::EmptyError(Json(Code{code : "From part of the request is empty".to_string()})));
     }
 
-    if transaction_parameters.to.is_empty(){
+    if to.is_empty(){
         return Err(ResponseError::EmptyError(Json(Code{code : "To part of the request is empty".to_string()})));
     }
 
-    let sender_address = Pubkey::from_str(&transaction_parameters.from[0].adress)
-    .map_err(|err| {
-        log::error!("Error during creating the Pubkey object from the sender address: {}", err);
-        ResponseError::CreatePubkeyError(Json(Code{code : "Failed during creating the Pubkey object from the sender address".to_string()}))
-    })?;
+    if from.len() > 1 && from.len() != to.len() {
+        return Err(ResponseError::InvalidSendersError(Json(Code{code : "Several senders have to be paired with as many recipients".to_string()})));
+    }
 
-    let privkey = transaction_parameters.private_key.clone();
+    let senders = from.iter()
+        .map(|sender| Pubkey::from_str(&sender.adress))
+        .collect::<Result<Vec<Pubkey>, _>>()
+        .map_err(|err| {
+            log::error!("Error during creating the Pubkey object from the sender address: {}", err);
+            ResponseError::CreatePubkeyError(Json(Code{code : "Failed during creating the Pubkey object from the sender address".to_string()}))
+        })?;
+    let senders = if senders.len() == 1 {
+        vec![senders[0]; to.len()]
+    } else {
+        senders
+    };
 
-    let mut bytes_of_privatekey = privkey.from_base58().map_err(|err|{
-        log::error!("Error during creating the byte array of private key: {}", err);
-        ResponseError::CreateByteArrayError(Json(Code{code: "Failed during creating the byte array of private key".to_string()}))
-    })?;
+    let fee_payer = match fee_payer {
+        Some(fee_payer) => Pubkey::from_str(fee_payer)
+            .map_err(|err| {
+                log::error!("Error during creating the Pubkey object from the fee payer address: {}", err);
+                ResponseError::CreatePubkeyError(Json(Code{code : "Failed during creating the Pubkey object from the fee payer address".to_string()}))
+            })?,
+        None => senders[0]
+    };
 
-    let mut bytes_of_publickey = transaction_parameters.from[0].adress.from_base58().map_err(|err|{
-        log::error!("Error during creating the byte array of public key: {}", err);
-        ResponseError::CreateByteArrayError(Json(Code{code: "Failed during creating the byte array of public key".to_string()}))
-    })?;
+    Ok(TransferAccounts { fee_payer, senders })
+}
 
-    bytes_of_privatekey.append(& mut bytes_of_publickey);
+/// Unsigned message of the transfers, with the last block height its blockhash is valid at
+async fn build_transfer_message(
+    accounts: &TransferAccounts,
+    to: &[AccountInfo],
+    create_associated_token_account: bool,
+    compute_budget: &ComputeBudget,
+    rpc_pool: &RpcPool,
+    config: &Config
+) -> Result<(Message, u64), ResponseError> {
+    check_compute_budget(compute_budget)?;
+    let (blockhash, last_valid_block_height) = get_latest_blockhash(rpc_pool, config).await?;
+    let TransferInstructions { mut instructions, .. } = build_transfer_instructions(accounts, to, create_associated_token_account, rpc_pool, config).await?;
 
-    let keypair: Keypair = Keypair::from_bytes(&bytes_of_privatekey)
-    .map_err(|err|{
-        log::error!("Error during creating the keypair object: {}", err);
-        ResponseError::CreateKeypairError(Json(Code{code: "Failed during creating the keypair object".to_string()}))
-    })?;
+    // The compute budget instructions are expected first, the automatic priority fee depends on the accounts of the transfers
+    let mut budget_instructions = compute_budget_instructions(compute_budget, &accounts.fee_payer, &instructions, rpc_pool, config).await?;
+    budget_instructions.append(&mut instructions);
+
+    Ok((Message::new_with_blockhash(&budget_instructions, Some(&accounts.fee_payer), &blockhash), last_valid_block_height))
+}
 
-    let blockhash = rpc_client.get_latest_blockhash()
+/// Latest blockhash, with the last block height it is valid at
+pub async fn get_latest_blockhash(rpc_pool: &RpcPool, config: &Config) -> Result<(Hash, u64), ResponseError> {
+    rpc_pool.call(|rpc_client| rpc_client.get_latest_blockhash_with_commitment(config.commitment())).await
     .map_err(|err| {
         log::error!("Error while getting the latest confirmed blockhash: {}", err);
         ResponseError::GetBlockhashError(Json(Code{code: "Failed during getting the latest confirmed blockhash".to_string()}))
-    })?;
+    })
+}
 
-    let instructions: Vec<Instruction> = transaction_parameters.to.iter()
-        .map(|transfer_param| {
-            let to_address = Pubkey::from_str(&transfer_param.adress)
-                .map_err(|err| {
-                    log::error!("Error during creating the Pubkey object from the receiver address: {}", err);
-                    ResponseError::CreatePubkeyError (Json( Code {code: "Failed during creating the Pubkey object from the receiver address".to_string() }))
-                })?;
-                
-            
-            let amount = &transfer_param.amount;
-
-            match transfer_param.contract.as_ref() {
-                Some(contract_str) => {
-
-                    let contract = Pubkey::from_str(contract_str)
-                        .map_err(|err| {
-                            log::error!("Error during creating the Pubkey object from the contract address: {}", err);
-                            ResponseError::CreatePubkeyError (Json(Code{ code: "Failed during creating the Pubkey object from the contract address".to_string() }))
-                        })?;
-                    
-
-
-                    let instruction = transfer(&spl_token::id(), 
-                    &contract, 
-                    &to_address, 
-                    &sender_address,
-                    &[&sender_address],
-                    *amount as u64)
-                        .map_err(|err| {
-                            log::error!("Error during creating the transaction instruction: {}", err);
-                            ResponseError::CreateTransferError (Json(Code{ code: "Failed during creating the transaction instruction".to_string() }))
-                        })?;
-                    Ok(instruction)
+/// Instructions of the transfers, without compute budget instructions
+pub struct TransferInstructions {
+    pub instructions: Vec<Instruction>,
+    /// Associated token accounts created by the instructions, with their size
+    pub created_accounts: Vec<(Pubkey, usize)>
+}
+
+pub async fn build_transfer_instructions(
+    accounts: &TransferAccounts,
+    to: &[AccountInfo],
+    create_associated_token_account: bool,
+    rpc_pool: &RpcPool,
+    config: &Config
+) -> Result<TransferInstructions, ResponseError> {
+    let mut instructions: Vec<Instruction> = Vec::with_capacity(to.len());
+    let mut created_accounts: Vec<(Pubkey, usize)> = Vec::new();
+    // Only needed for the transfer fees of Token-2022 mints
+    let mut epoch: Option<u64> = None;
+    for (transfer_param, sender_address) in to.iter().zip(accounts.senders.iter()) {
+        let to_address = Pubkey::from_str(&transfer_param.adress)
+            .map_err(|err| {
+                log::error!("Error during creating the Pubkey object from the receiver address: {}", err);
+                ResponseError::CreatePubkeyError (Json( Code {code: "Failed during creating the Pubkey object from the receiver address".to_string() }))
+            })?;
+
+        let amount = &transfer_param.amount;
+
+        let instruction = match transfer_param.contract.as_ref() {
+            Some(contract_str) => {
+
+                let contract = Pubkey::from_str(contract_str)
+                    .map_err(|err| {
+                        log::error!("Error during creating the Pubkey object from the contract address: {}", err);
+                        ResponseError::CreatePubkeyError (Json(Code{ code: "Failed during creating the Pubkey object from the contract address".to_string() }))
+                    })?;
+
+                let mint = get_mint(rpc_pool, &contract, config.commitment).await?;
+                // The raw amount would be misread if the caller assumed other decimals than the mint's
+                check_decimals(amount, mint.decimals)?;
+                // The UI amount of interest-bearing mints grows with time, only the raw amount is exact
+                if mint.interest_bearing_config.is_some() && amount.ui_amount_string.is_some() {
+                    log::error!("Amount of the interest-bearing mint {} is given with uiAmountString", contract);
+                    return Err(ResponseError::InvalidAmountError(Json(Code{ code: format!("Amounts of the interest-bearing mint {} have to be given as raw amount without uiAmountString", contract) })));
                 }
-                None => Ok(solana_sdk::system_instruction::transfer(&sender_address, &to_address, *amount as u64))
+
+                // Tokens are held by the associated token accounts of the wallets, not by the wallets themselves
+                let source = get_associated_token_address_with_program_id(sender_address, &contract, &mint.program_id);
+                let destination = get_associated_token_address_with_program_id(&to_address, &contract, &mint.program_id);
+
+                if create_associated_token_account
+                    && !created_accounts.iter().any(|(created_account, _)| *created_account == destination)
+                    && !account_exists(rpc_pool, &destination, config.commitment).await? {
+                    instructions.push(create_associated_token_account_idempotent(&accounts.fee_payer, &to_address, &contract, &mint.program_id));
+                    created_accounts.push((destination, mint.associated_token_account_len()));
+                }
+
+                if mint.transfer_fee_config.is_some() && epoch.is_none() {
+                    epoch = Some(get_epoch(rpc_pool, config.commitment).await?);
+                }
+                mint.transfer_checked(&source, &destination, sender_address, amount.amount, epoch.unwrap_or_default())?
+            }
+            None => {
+                check_decimals(amount, SOL_PRECISION)?;
+                solana_sdk::system_instruction::transfer(sender_address, &to_address, amount.amount)
             }
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-        
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&sender_address),
-            &[&keypair],
-            blockhash
-        );
-        
-        let signatures = &tx.signatures;
-        let txn_hash = signatures[0].to_string();
-    
-        let signed_transaction = serde_json::to_string(&tx)
-        .map_err(|err| {
-            log::error!("Error during converting the Transaction object to String: {}", err);
-            ResponseError::ConvertTransactionError (Json(Code{ code: "Failed during converting Transaction object to String".to_string() }))
-        })?;
-        let response: SignTransactionResponse = SignTransactionResponse{
-            txn_hash,
-            signed_transaction
         };
-        
-        Ok(Json(response))
+        instructions.push(instruction);
+    }
 
+    Ok(TransferInstructions { instructions, created_accounts })
 }
 
-#[get("/transactions/<txn_hash>/detail")]
-pub fn get_transaction_details(
-    rpc_client: &State<Arc<RpcClient>>,
-    txn_hash: &str
-) -> Result<Json<TransactionInfo>, ResponseError> {
+fn decode_message(message: &str) -> Result<Message, ResponseError> {
+    let bytes = BASE64.decode(message)
+        .map_err(|err| {
+            log::error!("Error while decoding the message: {}", err);
+            ResponseError::InvalidMessageError(Json(Code{ code: "Message is not valid base64".to_string() }))
+        })?;
+    let message: Message = bincode::deserialize(&bytes)
+        .map_err(|err| {
+            log::error!("Error while deserializing the message: {}", err);
+            ResponseError::InvalidMessageError(Json(Code{ code: "Message is not a valid transaction message".to_string() }))
+        })?;
+    message.sanitize()
+        .map_err(|err| {
+            log::error!("Error while validating the message: {}", err);
+            ResponseError::InvalidMessageError(Json(Code{ code: "Message is not a valid transaction message".to_string() }))
+        })?;
+    Ok(message)
+}
 
-    let signature = Signature::from_str(txn_hash)
+/// Transaction that has been signed at least partially, missing signatures are left zeroed
+fn decode_partially_signed_transaction(signed_transaction: &str) -> Result<(Message, Vec<Option<Signature>>), ResponseError> {
+    let tx = serde_json::from_str::<Transaction>(signed_transaction)
         .map_err(|err| {
-            log::error!("Failed during converting txnHash (&str) to Signature: {}", err);
-            ResponseError::StrToSignatureError (Json(Code{ code: "Failed during parsing signature".to_string() }))
+            log::error!("Error while creating the transaction object: {}", err);
+            ResponseError::InvalidMessageError(Json(Code{ code: "Signed transaction is not a valid transaction".to_string() }))
         })?;
-    
-    let conf_transaction = rpc_client.get_transaction(&signature, UiTransactionEncoding::Json)
+    tx.sanitize()
+        .map_err(|err| {
+            log::error!("Error while validating the transaction: {}", err);
+            ResponseError::InvalidMessageError(Json(Code{ code: "Signed transaction is not a valid transaction".to_string() }))
+        })?;
+
+    let message_data = tx.message.serialize();
+    let signatures = tx.signatures.iter().zip(tx.message.signer_keys())
+        .map(|(signature, signer)| {
+            if *signature == Signature::default() {
+                Ok(None)
+            } else if signature.verify(signer.as_ref(), &message_data) {
+                Ok(Some(*signature))
+            } else {
+                Err(ResponseError::InvalidSignatureError(Json(Code{ code: format!("Signature of {} does not match the message", signer) })))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((tx.message, signatures))
+}
+
+fn signed_transaction_response(tx: Transaction, missing_signatures: Vec<String>) -> Result<SignTransactionResponse, ResponseError> {
+    // The hash of a transaction is the signature of its fee payer
+    let txn_hash = tx.signatures.first()
+        .filter(|signature| **signature != Signature::default())
+        .map(|signature| signature.to_string());
+
+    let signed_transaction = serde_json::to_string(&tx)
+    .map_err(|err| {
+        log::error!("Error during converting the Transaction object to String: {}", err);
+        ResponseError::ConvertTransactionError (Json(Code{ code: "Failed during converting Transaction object to String".to_string() }))
+    })?;
+
+    Ok(SignTransactionResponse{
+        txn_hash,
+        signed_transaction,
+        missing_signatures
+    })
+}
+
+fn check_decimals(amount: &Amount, decimals: u8) -> Result<(), ResponseError> {
+    if amount.decimals != decimals {
+        log::error!("Amount is given with {} decimals instead of {}", amount.decimals, decimals);
+        return Err(ResponseError::InvalidAmountError(Json(Code{ code: format!("Amount has to be given with {} decimals", decimals) })));
+    }
+    Ok(())
+}
+
+/// Fetches the transaction together with the block it was included in
+pub async fn get_transaction_info(rpc_pool: &RpcPool, signature: &Signature, commitment: Commitment) -> Result<TransactionInfo, ResponseError> {
+    let conf_transaction = rpc_pool.call(|rpc_client| rpc_client.get_transaction_with_config(signature, RpcTransactionConfig {encoding: Some(UiTransactionEncoding::Json), commitment: Some(commitment.to_commitment_config()), max_supported_transaction_version: Some(0)})).await
         .map_err(|err| {
             log::error!("Failed during getting the transaction with given hash: {}", err);
             ResponseError::GetTransactionError (Json(Code{ code: "Failed during getting the transaction with given hash".to_string() }))
         })?;
 
     let block_slot = conf_transaction.slot;
-    let block_hash = rpc_client.get_block_with_config(block_slot, RpcBlockConfig {encoding: Some(UiTransactionEncoding::Json), transaction_details: Some(TransactionDetails::Full), rewards: Some(false), commitment: Some(CommitmentConfig::finalized()), max_supported_transaction_version: Some(0)})
-        .map(|block| block.blockhash)
+    let block = rpc_pool.call(|rpc_client| rpc_client.get_block_with_config(block_slot, RpcBlockConfig {encoding: Some(UiTransactionEncoding::Json), transaction_details: Some(TransactionDetails::None), rewards: Some(false), commitment: Some(commitment.to_commitment_config()), max_supported_transaction_version: Some(0)})).await
+        .map(|block| BlockReference {
+            slot: block_slot,
+            block_height: block.block_height,
+            block_time: block.block_time,
+            hash: block.blockhash
+        })
         .map_err(|err| {
             log::error!("Failed during getting the block with given slot: {}", err);
             ResponseError::GetBlockError (Json(Code{ code: "Failed during getting the block with given slot".to_string() }))
@@ -146,14 +427,37 @@ pub fn get_transaction_details(
 
     let transaction_meta = conf_transaction.transaction;
 
-    let transaction = transaction_meta.to_transaction_info(block_slot, &block_hash)?;
-    Ok(Json(transaction))
+    transaction_meta.to_transaction_info(&block)
+}
+
+#[get("/transactions/<txn_hash>/detail?<commitment>")]
+pub async fn get_transaction_details(
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>,
+    txn_hash: &str,
+    commitment: Option<Commitment>,
+    version: ApiVersion
+) -> Result<Json<TransactionInfo>, ResponseError> {
+    let commitment = commitment.unwrap_or(config.commitment).for_blocks();
+
+    let signature = Signature::from_str(txn_hash)
+        .map_err(|err| {
+            log::error!("Failed during converting txnHash (&str) to Signature: {}", err);
+            ResponseError::StrToSignatureError (Json(Code{ code: "Failed during parsing signature".to_string() }))
+        })?;
+
+    let mut transaction = get_transaction_info(rpc_pool, &signature, commitment).await?;
+    transaction.commitment = Some(commitment);
+    Ok(Json(transaction.versioned(version)))
 }
 
-#[post("/transactions/send", data = "<transaction_parameters>")]
-pub fn send_transaction(
+/// Returns as soon as the transaction is sent, or once it reaches the waitFor commitment
+#[post("/transactions/send?<query..>", data = "<transaction_parameters>")]
+pub async fn send_transaction(
     transaction_parameters: Json<SendTransactionRequest>,
-    rpc_client: &State<Arc<RpcClient>>
+    query: SendTransactionQuery,
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>
 ) -> Result<Json<SendTransactionResponse>, ResponseError> {
 
     let tx = serde_json::from_str::<Transaction>(&transaction_parameters.signed_transaction)
@@ -162,38 +466,297 @@ pub fn send_transaction(
             ResponseError::CreateTransactionError (Json(Code{ code: "Failed during creating the transaction object".to_string() }))
         })?;
 
-    rpc_client
-        .send_and_confirm_transaction(&tx)
-        .map(|txn_hash|
-            Json(SendTransactionResponse{
-                txn_hash: txn_hash.to_string()
-            })
-        )
+    let signature = rpc_pool
+        .call(|rpc_client| rpc_client.send_transaction(&tx)).await
         .map_err(|err| {
+            // The preflight simulation of the node tells why the transaction would fail
+            if let ClientErrorKind::RpcError(RpcError::RpcResponseError { data: RpcResponseErrorData::SendTransactionPreflightFailure(result), .. }) = err.kind() {
+                if let Some(transaction_error) = &result.err {
+                    return ResponseError::SimulationFailedError(Json(Code{ code: format!("Transaction fails in simulation: {}", describe_transaction_error(transaction_error, &tx.message)) }));
+                }
+            }
             log::error!("Error while sending the transaction: {}", err);
             ResponseError::SendTransactionError (Json(Code{ code: "Failed during sending the transaction".to_string() }))
+        })?;
+
+    let status = match query.wait_for {
+        Some(commitment) => wait_for_status(&signature, commitment, &tx.message, rpc_pool, config).await?,
+        None => None
+    };
+
+    Ok(Json(SendTransactionResponse {
+        txn_hash: signature.to_string(),
+        status
+    }))
+}
+
+/// Polls the status of a sent transaction until it reaches the commitment or fails, at most for the confirmation timeout
+async fn wait_for_status(
+    signature: &Signature,
+    commitment: Commitment,
+    message: &Message,
+    rpc_pool: &RpcPool,
+    config: &Config
+) -> Result<Option<TransactionStatus>, ResponseError> {
+    let deadline = Instant::now() + config.confirm_timeout();
+    loop {
+        let status = get_signature_status(rpc_pool, signature, false).await?;
+        let done = status.as_ref()
+            .map(|status| status.err.is_some() || status.satisfies_commitment(commitment.to_commitment_config()))
+            .unwrap_or_default();
+        if done || Instant::now() >= deadline {
+            return Ok(status.map(|status| to_transaction_status(signature, status, Some(message))));
+        }
+        sleep(SIGNATURE_STATUS_POLL_INTERVAL).await;
+    }
+}
+
+/// Status of the transaction, None while it is not known by the node
+async fn get_signature_status(rpc_pool: &RpcPool, signature: &Signature, search_transaction_history: bool) -> Result<Option<SignatureStatus>, ResponseError> {
+    let signatures = [*signature];
+    let statuses = if search_transaction_history {
+        rpc_pool.call(|rpc_client| rpc_client.get_signature_statuses_with_history(&signatures)).await
+    } else {
+        rpc_pool.call(|rpc_client| rpc_client.get_signature_statuses(&signatures)).await
+    };
+
+    statuses
+        .map(|response| response.value.into_iter().next().flatten())
+        .map_err(|err| {
+            log::error!("Failed during getting the status of the transaction: {}", err);
+            ResponseError::GetSignatureStatusError(Json(Code{ code: "Failed during getting the status of the transaction".to_string() }))
+        })
+}
+
+/// The error is only decoded when the message of the transaction is known
+fn to_transaction_status(signature: &Signature, status: SignatureStatus, message: Option<&Message>) -> TransactionStatus {
+    TransactionStatus {
+        txn_hash: signature.to_string(),
+        slot: status.slot,
+        confirmations: status.confirmations,
+        confirmation_status: status.confirmation_status.map(Commitment::from),
+        status: if status.err.is_none() { "Success".to_string() } else { "Failed".to_string() },
+        error: status.err.map(|err| match message {
+            Some(message) => describe_transaction_error(&err, message),
+            None => err.to_string()
         })
+    }
 }
 
-#[get("/transactions/<txn_hash>/confirmations")]
-pub fn get_confirmation_count(    
-    rpc_client: &State<Arc<RpcClient>>,
+#[get("/transactions/<txn_hash>/status")]
+pub async fn get_transaction_status(
+    rpc_pool: &State<Arc<RpcPool>>,
     txn_hash: &str
+) -> Result<Json<TransactionStatus>, ResponseError> {
+    let signature = Signature::from_str(txn_hash)
+        .map_err(|err| {
+            log::error!("Failed during converting txnHash (&str) to Signature: {}", err);
+            ResponseError::StrToSignatureError (Json(Code{ code: "Failed during parsing signature".to_string() }))
+        })?;
+
+    // Transactions older than the status cache of the node are looked up in the ledger
+    let status = get_signature_status(rpc_pool, &signature, true).await?
+        .ok_or_else(|| ResponseError::TransactionNotFoundError(Json(Code{ code: format!("Transaction {} is not known by the cluster", txn_hash) })))?;
+
+    Ok(Json(to_transaction_status(&signature, status, None)))
+}
+
+/// Dry-runs a signed transaction, or a built message without verifying its signatures
+#[post("/transactions/simulate", data = "<transaction_parameters>")]
+pub async fn simulate_transaction(
+    transaction_parameters: Json<SimulateTransactionRequest>,
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>
+) -> Result<Json<SimulationResult>, ResponseError> {
+    let tx = match (&transaction_parameters.signed_transaction, &transaction_parameters.message) {
+        (Some(signed_transaction), None) => serde_json::from_str::<Transaction>(signed_transaction)
+            .map_err(|err|{
+                log::error!("Error while creating the transaction object: {}", err);
+                ResponseError::CreateTransactionError (Json(Code{ code: "Failed during creating the transaction object".to_string() }))
+            })?,
+        (None, Some(message)) => Transaction::new_unsigned(decode_message(message)?),
+        _ => {
+            return Err(ResponseError::InvalidMessageError(Json(Code{ code: "Exactly one of signedTransaction and message has to be given".to_string() })));
+        }
+    };
+    tx.sanitize()
+        .map_err(|err| {
+            log::error!("Error while validating the transaction: {}", err);
+            ResponseError::InvalidMessageError(Json(Code{ code: "Transaction is not a valid transaction".to_string() }))
+        })?;
+
+    // Built messages and partially signed transactions are simulated without their signatures, on the latest blockhash
+    let signed = tx.signatures.iter().all(|signature| *signature != Signature::default());
+    let account_keys = tx.message.account_keys.clone();
+    let pre_accounts = rpc_pool.call(|rpc_client| rpc_client.get_multiple_accounts_with_commitment(&account_keys, config.commitment())).await
+        .map(|response| response.value)
+        .map_err(|err| {
+            log::error!("Failed during getting the accounts of the transaction: {}", err);
+            ResponseError::GetAccountError(Json(Code{ code: "Failed during getting the accounts of the transaction".to_string() }))
+        })?;
+
+    let simulation_config = RpcSimulateTransactionConfig {
+        sig_verify: signed,
+        replace_recent_blockhash: !signed,
+        commitment: Some(config.commitment()),
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: account_keys.iter().map(|account_key| account_key.to_string()).collect()
+        }),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let simulation = rpc_pool.call(|rpc_client| rpc_client.simulate_transaction_with_config(&tx, simulation_config.clone())).await
+        .map(|response| response.value)
+        .map_err(|err| {
+            log::error!("Failed during simulating the transaction: {}", err);
+            ResponseError::SimulateTransactionError(Json(Code{ code: "Failed during simulating the transaction".to_string() }))
+        })?;
+
+    // Failed simulations do not return the state of the accounts
+    let (from, to) = match &simulation.accounts {
+        Some(post_accounts) if simulation.err.is_none() => {
+            let post_accounts: Vec<Option<Account>> = post_accounts.iter()
+                .map(|account| account.as_ref().and_then(|account| account.decode::<Account>()))
+                .collect();
+            simulated_balance_changes(&account_keys, &pre_accounts, &post_accounts, rpc_pool, config).await?
+        },
+        _ => (Vec::new(), Vec::new())
+    };
+
+    Ok(Json(SimulationResult {
+        from,
+        to,
+        status: if simulation.err.is_none() { "Success".to_string() } else { "Failed".to_string() },
+        error: simulation.err.as_ref().map(|err| describe_transaction_error(err, &tx.message)),
+        logs: simulation.logs.unwrap_or_default(),
+        units_consumed: simulation.units_consumed.unwrap_or_default()
+    }))
+}
+
+/// SOL balance changes of the accounts, then the token balance changes of the token accounts, like for executed transactions
+async fn simulated_balance_changes(
+    account_keys: &[Pubkey],
+    pre_accounts: &[Option<Account>],
+    post_accounts: &[Option<Account>],
+    rpc_pool: &RpcPool,
+    config: &Config
+) -> Result<(Vec<AccountInfo>, Vec<AccountInfo>), ResponseError> {
+    let mut from: Vec<AccountInfo> = Vec::new();
+    let mut to: Vec<AccountInfo> = Vec::new();
+    let accounts: Vec<(&Pubkey, Option<&Account>, Option<&Account>)> = account_keys.iter()
+        .zip(pre_accounts.iter())
+        .zip(post_accounts.iter())
+        .map(|((address, pre_account), post_account)| (address, pre_account.as_ref(), post_account.as_ref()))
+        .collect();
+
+    for (address, pre_account, post_account) in accounts.iter() {
+        let pre_balance = pre_account.map(|account| account.lamports).unwrap_or_default();
+        let post_balance = post_account.map(|account| account.lamports).unwrap_or_default();
+        push_balance_change(&mut from, &mut to, &address.to_string(), pre_balance, post_balance, SOL_PRECISION, None);
+    }
+
+    let mut decimals_by_mint: HashMap<Pubkey, u8> = HashMap::new();
+    for (address, pre_account, post_account) in accounts.iter() {
+        let pre_token_account = pre_account.and_then(unpack_token_account);
+        let post_token_account = post_account.and_then(unpack_token_account);
+        // Token accounts closed by the transaction only have a pre state, created ones only a post state
+        let (mint, owner) = match post_token_account.or(pre_token_account) {
+            Some(token_account) => (token_account.mint, token_account.owner),
+            None => continue
+        };
+
+        let decimals = match decimals_by_mint.get(&mint) {
+            Some(decimals) => *decimals,
+            None => {
+                let decimals = get_mint(rpc_pool, &mint, config.commitment).await?.decimals;
+                decimals_by_mint.insert(mint, decimals);
+                decimals
+            }
+        };
+        let pre_amount = pre_token_account.map(|token_account| token_account.amount).unwrap_or_default();
+        let post_amount = post_token_account.map(|token_account| token_account.amount).unwrap_or_default();
+        let token = TokenBalanceOwner { mint: mint.to_string(), owner: Some(owner.to_string()) };
+        push_balance_change(&mut from, &mut to, &address.to_string(), pre_amount, post_amount, decimals, Some(token));
+    }
+
+    Ok((from, to))
+}
+
+/// Readable reason of a failed transaction, with the custom errors of the system, token and associated token account programs decoded
+pub fn describe_transaction_error(err: &TransactionError, message: &Message) -> String {
+    match err {
+        TransactionError::InstructionError(index, instruction_error) => {
+            let program_id = message.instructions.get(usize::from(*index))
+                .and_then(|instruction| message.account_keys.get(usize::from(instruction.program_id_index)));
+            let reason = match (instruction_error, program_id) {
+                (InstructionError::Custom(code), Some(program_id)) => describe_custom_error(program_id, *code)
+                    .unwrap_or_else(|| instruction_error.to_string()),
+                _ => instruction_error.to_string()
+            };
+            match program_id {
+                Some(program_id) => format!("Instruction {} ({}) failed: {}", index, program_name(program_id), reason),
+                None => format!("Instruction {} failed: {}", index, reason)
+            }
+        },
+        err => err.to_string()
+    }
+}
+
+fn describe_custom_error(program_id: &Pubkey, code: u32) -> Option<String> {
+    if *program_id == solana_sdk::system_program::id() {
+        SystemError::from_u32(code).map(|err| err.to_string())
+    } else if *program_id == spl_token::id() {
+        spl_token::error::TokenError::from_u32(code).map(|err| err.to_string())
+    } else if *program_id == spl_token_2022::id() {
+        spl_token_2022::error::TokenError::from_u32(code).map(|err| err.to_string())
+    } else if *program_id == spl_associated_token_account::id() {
+        AssociatedTokenAccountError::from_u32(code).map(|err| err.to_string())
+    } else {
+        None
+    }
+}
+
+fn program_name(program_id: &Pubkey) -> String {
+    let name = if *program_id == solana_sdk::system_program::id() {
+        "System Program"
+    } else if *program_id == spl_token::id() {
+        "Token Program"
+    } else if *program_id == spl_token_2022::id() {
+        "Token-2022 Program"
+    } else if *program_id == spl_associated_token_account::id() {
+        "Associated Token Account Program"
+    } else if *program_id == solana_sdk::compute_budget::id() {
+        "Compute Budget Program"
+    } else {
+        return format!("program {}", program_id);
+    };
+    name.to_string()
+}
+
+#[get("/transactions/<txn_hash>/confirmations?<commitment>")]
+pub async fn get_confirmation_count(    
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>,
+    txn_hash: &str,
+    commitment: Option<Commitment>
 ) -> Result<Json<ConfirmationCount>, ResponseError> {
+    let commitment = commitment.unwrap_or(config.commitment);
+    let block_commitment = commitment.for_blocks().to_commitment_config();
+
     let signature = Signature::from_str(txn_hash)
         .map_err(|err| {
             log::error!("Failed during converting txnHash (&str) to Signature: {}", err);
             ResponseError::StrToSignatureError (Json(Code{ code: "Failed during parsing signature".to_string() }))
         })?;
     
-    let block_slot = rpc_client.get_transaction(&signature, UiTransactionEncoding::Json)
+    let block_slot = rpc_pool.call(|rpc_client| rpc_client.get_transaction_with_config(&signature, RpcTransactionConfig {encoding: Some(UiTransactionEncoding::Json), commitment: Some(block_commitment), max_supported_transaction_version: Some(0)})).await
         .map(|transaction| transaction.slot)
         .map_err(|err| {
             log::error!("Failed during getting the transaction with given hash: {}", err);
             ResponseError::GetTransactionError (Json(Code{ code: "Failed during getting the transaction with given hash".to_string() }))
         })?;
     
-    let block = rpc_client.get_block_with_config(block_slot, RpcBlockConfig {encoding: Some(UiTransactionEncoding::Json), transaction_details: Some(TransactionDetails::Full), rewards: Some(false), commitment: Some(CommitmentConfig::finalized()), max_supported_transaction_version: Some(0)})
+    let block = rpc_pool.call(|rpc_client| rpc_client.get_block_with_config(block_slot, RpcBlockConfig {encoding: Some(UiTransactionEncoding::Json), transaction_details: Some(TransactionDetails::None), rewards: Some(false), commitment: Some(block_commitment), max_supported_transaction_version: Some(0)})).await
         .map_err(|err| {
             log::error!("Failed during getting the block with given slot: {}", err);
             ResponseError::GetBlockError (Json(Code{ code: "Failed during getting the block with given slot".to_string() }))
@@ -208,14 +771,16 @@ pub fn get_confirmation_count(
         }
     };
 
-    let latest_block_height = rpc_client.get_block_height()
+    let latest_block_height = rpc_pool.call(|rpc_client| rpc_client.get_block_height_with_commitment(commitment.to_commitment_config())).await
         .map_err(|err| {
             log::error!("Failed during getting the latest block height: {}", err);
             ResponseError::GetBlockHeightError (Json(Code{ code: "Failed during getting the latest block height".to_string() }))
         })?;
 
     Ok(Json(ConfirmationCount { 
-        confirmations_count: latest_block_height - block_height
+        // The latest height can be behind the block when queried with a higher commitment
+        confirmations_count: latest_block_height.saturating_sub(block_height),
+        commitment
     }))
 
 }
\ No newline at end of file
@@ -1,14 +1,16 @@
-use crate::{errors::{ResponseError, Code}, models::{Balance, WalletResponse, adjust_precision}};
-use solana_client::{rpc_client::RpcClient, rpc_request::TokenAccountsFilter};
+use crate::{config::{Commitment, Config, SOL_PRECISION}, errors::{ResponseError, Code}, models::{AddressTransaction, AddressTransactions, Amount, ApiVersion, Balance, CreateHdWalletRequest, CreateWalletRequest, DeriveAddressRequest, HdWalletInfo, KeyInfo, KeyList, Portfolio, TokenAccountBalance, TokenHolding, UpdateKeyRequest, WalletResponse}, pool::RpcPool, queries::AddressTransactionsQuery, signers::SignerBackend, tokens::{MintInfo, get_mint}, transactions::get_transaction_info};
+use solana_client::{rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_request::TokenAccountsFilter, rpc_response::RpcKeyedAccount};
 use std::sync::Arc;
-use rocket::{State, serde::json::Json};
+use rocket::{State, futures::{StreamExt, stream}, serde::json::Json};
 use bs58;
-use solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use solana_sdk::{pubkey::Pubkey, signature::{Keypair, Signature, generate_seed_from_seed_phrase_and_passphrase}};
+use bip39::{Language, Mnemonic, MnemonicType};
 use std::str::FromStr;
-use solana_account_decoder::UiAccountData;
+use solana_account_decoder::{UiAccountData, parse_token::{TokenAccountType, UiAccountState}};
 
-#[get("/address/<address>/balance?<contract>")]
-pub fn get_wallet_balance(address: &str, contract: Option<String>,rpc_client: &State<Arc<RpcClient>>) -> Result<Json<Balance>, ResponseError>{
+#[get("/address/<address>/balance?<contract>&<commitment>")]
+pub async fn get_wallet_balance(address: &str, contract: Option<String>, commitment: Option<Commitment>, rpc_pool: &State<Arc<RpcPool>>, config: &State<Config>) -> Result<Json<Balance>, ResponseError>{
+    let commitment = commitment.unwrap_or(config.commitment);
 
     let pubkey = Pubkey::from_str(address)
             .map_err(|err| {
@@ -16,7 +18,7 @@ pub fn get_wallet_balance(address: &str, contract: Option<String>,rpc_client: &S
             ResponseError::CreatePubkeyError(Json(Code{code: "Error while creating the Pubkey object from owner address".to_string()}))
     })?;
 
-    let balance: f64 = match contract {
+    let (balance, accounts): (Amount, Option<Vec<TokenAccountBalance>>) = match contract {
         Some(c_address) =>{
 
             let contract_address = Pubkey::from_str(&c_address)
@@ -26,82 +28,322 @@ pub fn get_wallet_balance(address: &str, contract: Option<String>,rpc_client: &S
             })?;
             
             
-            let rpc_account = rpc_client.get_token_accounts_by_owner(&pubkey, TokenAccountsFilter::Mint(contract_address))
+            let rpc_accounts = rpc_pool.call(|rpc_client| rpc_client.get_token_accounts_by_owner_with_commitment(&pubkey, TokenAccountsFilter::Mint(contract_address), commitment.to_commitment_config())).await
+                        .map(|response| response.value)
                         .map_err(|err| {
                         log::error!("Failed during getting the balance: {}", err);
                         ResponseError::GetBalanceError (Json(Code{ code : "Failed during getting the balance of a wallet".to_string() }))
                     })?;
 
-            if rpc_account.is_empty(){
-                0 as f64
-            }
-            
-            else{
-                log::info!("len vec: {}", rpc_account.len());
-
-                let parsed_account = match rpc_account[0].account.data.clone(){
-                    UiAccountData::Json(parsed_account) => parsed_account,
-                    _ => {
-                        return Err(ResponseError::UiAccountDataTypeError(Json(Code{ code: "UiAccountData type Binary and LegacyBinary not implemented".to_string() })));
-                    }
-                };
-
-                match parsed_account.parsed.get("info"){
-                    Some(info) => {
-                        match info.get("tokenAmount"){
-                            Some(token_amount) => {
-                                match token_amount.get("uiAmount"){
-                                    Some(ui_amount) => {
-                                        match ui_amount.as_f64(){
-                                            Some(amount) => amount,
-                                            None => {
-                                                log::error!("Error while converting UiAmount to f64");
-                                                return Err(ResponseError::ConvertUiAmountError(Json(Code{code: "Error while converting UiAmount to f64".to_string()})));    
-                                            }
-                                        }
-                                    },
-                                    None => {
-                                        log::error!("Error: couldn't get the uiAmount from the parsed account.");
-                                        return Err(ResponseError::EmptyError(Json(Code{code: "Error: couldn't get the uiAmount from the parsed account.".to_string()})));
-                                    }
-                                }
-                            },
-                            None => {
-                                log::error!("Error: couldn't get the tokenAmount from the parsed account.");
-                                return Err(ResponseError::EmptyError(Json(Code{code: "Error while converting tokenAmount to f64".to_string()})));
-                            }
-                        }
-                    },
-                    None => {
-                        log::error!("Error: couldn't get the info from the parsed account.");
-                        return Err(ResponseError::EmptyError(Json(Code{code: "Error: couldn't get the info from the parsed account.".to_string()})));
-                    }
+            let mut accounts = rpc_accounts.iter()
+                .map(|rpc_account| parse_token_account(rpc_account).map(|(_, account)| account))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // Without a token account the decimals are only known by the mint, Token-2022 mints may accrue interest
+            let is_token_2022 = rpc_accounts.iter().any(|rpc_account| rpc_account.account.owner == spl_token_2022::id().to_string());
+            let balance = match accounts.first() {
+                Some(account) if !is_token_2022 => total_balance(&accounts, account.amount.decimals)?,
+                _ => {
+                    let mint = get_mint(rpc_pool, &contract_address, commitment).await?;
+                    apply_interest(&mut accounts, &mint);
+                    mint.amount(total_balance(&accounts, mint.decimals)?.amount)
                 }
-            }
+            };
+
+            (balance, Some(accounts))
         },
-        None => {
-            adjust_precision(rpc_client.get_balance(&pubkey)
-                        .map_err(|err| {
-                        log::error!("Failed during getting the balance: {}", err);
-                        ResponseError::GetBalanceError (Json(Code{ code : "Failed during getting the balance of a wallet".to_string() }))
-            })? as f64)
-        }
+        None => (get_sol_balance(rpc_pool, &pubkey, commitment).await?, None)
     };
 
-    let response: Balance = Balance{balance};
+    let response: Balance = Balance{balance, accounts, commitment};
 
     Ok(Json(response))
 }
 
-#[post("/address")]
-pub fn create_wallet_address() -> Json<WalletResponse>{
-    let keypair = Keypair::new();
-    let byte_array = keypair.to_bytes();
-    let key_length = 32;
-    let address = bs58::encode(&byte_array[key_length..]).into_string();
-    let private_key = bs58::encode(&byte_array[0..key_length]).into_string();
+/// Reads the mint and the balance of a token account of either token program returned with the jsonParsed encoding
+fn parse_token_account(keyed_account: &RpcKeyedAccount) -> Result<(String, TokenAccountBalance), ResponseError> {
+    let parsed_account = match &keyed_account.account.data {
+        UiAccountData::Json(parsed_account) => parsed_account,
+        _ => {
+            return Err(ResponseError::UiAccountDataTypeError(Json(Code{ code: "UiAccountData type Binary and LegacyBinary not implemented".to_string() })));
+        }
+    };
+
+    let token_account = match serde_json::from_value::<TokenAccountType>(parsed_account.parsed.clone()) {
+        Ok(TokenAccountType::Account(token_account)) => token_account,
+        Ok(_) => {
+            log::error!("Error: account {} is not a token account.", keyed_account.pubkey);
+            return Err(ResponseError::UiAccountDataTypeError(Json(Code{code: "Parsed account is not a token account".to_string()})));
+        },
+        Err(err) => {
+            log::error!("Error while parsing the token account {}: {}", keyed_account.pubkey, err);
+            return Err(ResponseError::ConvertUiAmountError(Json(Code{code: "Error while parsing the token account".to_string()})));
+        }
+    };
+
+    let amount = token_account.token_amount.amount.parse::<u64>()
+        .map_err(|err| {
+            log::error!("Error while converting the token amount {}: {}", token_account.token_amount.amount, err);
+            ResponseError::ConvertUiAmountError(Json(Code{code: "Error while converting the token amount".to_string()}))
+        })?;
+
+    Ok((token_account.mint, TokenAccountBalance {
+        address: keyed_account.pubkey.clone(),
+        amount: Amount::new(amount, token_account.token_amount.decimals),
+        frozen: token_account.state == UiAccountState::Frozen,
+        delegate: token_account.delegate
+    }))
+}
+
+fn total_balance(accounts: &[TokenAccountBalance], decimals: u8) -> Result<Amount, ResponseError> {
+    let mut total: u64 = 0;
+    for account in accounts.iter() {
+        total = total.checked_add(account.amount.amount)
+            .ok_or_else(|| ResponseError::BalanceAmountError(Json(Code{code: "Token balance overflows".to_string()})))?;
+    }
+    Ok(Amount::new(total, decimals))
+}
+
+/// UI amounts of interest-bearing mints include the interest accrued until now, whatever the version of the node
+fn apply_interest(accounts: &mut [TokenAccountBalance], mint: &MintInfo) {
+    for account in accounts.iter_mut() {
+        account.amount = mint.amount(account.amount.amount);
+    }
+}
+
+async fn get_sol_balance(rpc_pool: &RpcPool, pubkey: &Pubkey, commitment: Commitment) -> Result<Amount, ResponseError> {
+    rpc_pool.call(|rpc_client| rpc_client.get_balance_with_commitment(pubkey, commitment.to_commitment_config())).await
+        .map(|response| Amount::new(response.value, SOL_PRECISION))
+        .map_err(|err| {
+            log::error!("Failed during getting the balance: {}", err);
+            ResponseError::GetBalanceError (Json(Code{ code : "Failed during getting the balance of a wallet".to_string() }))
+        })
+}
+
+#[get("/address/<address>/tokens?<commitment>")]
+pub async fn get_wallet_tokens(address: &str, commitment: Option<Commitment>, rpc_pool: &State<Arc<RpcPool>>, config: &State<Config>) -> Result<Json<Portfolio>, ResponseError> {
+    let commitment = commitment.unwrap_or(config.commitment);
+
+    let pubkey = Pubkey::from_str(address)
+        .map_err(|err| {
+            log::error!("Error while creating the Pubkey object from owner address: {}", err);
+            ResponseError::CreatePubkeyError(Json(Code{code: "Error while creating the Pubkey object from owner address".to_string()}))
+        })?;
+
+    let balance = get_sol_balance(rpc_pool, &pubkey, commitment).await?;
+
+    let mut tokens: Vec<TokenHolding> = Vec::new();
+    for program_id in [spl_token::id(), spl_token_2022::id()] {
+        let rpc_accounts = rpc_pool.call(|rpc_client| rpc_client.get_token_accounts_by_owner_with_commitment(&pubkey, TokenAccountsFilter::ProgramId(program_id), commitment.to_commitment_config())).await
+            .map(|response| response.value)
+            .map_err(|err| {
+                log::error!("Failed during getting the token accounts: {}", err);
+                ResponseError::GetBalanceError (Json(Code{ code : "Failed during getting the token accounts of a wallet".to_string() }))
+            })?;
+
+        // Token accounts are grouped by mint, in the order the node returned them
+        let mut holdings: Vec<(String, Vec<TokenAccountBalance>)> = Vec::new();
+        for rpc_account in rpc_accounts.iter() {
+            let (mint, account) = parse_token_account(rpc_account)?;
+            match holdings.iter_mut().find(|(contract, _)| *contract == mint) {
+                Some((_, accounts)) => accounts.push(account),
+                None => holdings.push((mint, vec![account]))
+            }
+        }
+
+        for (contract, mut accounts) in holdings {
+            // Every holding has at least one account, all of them with the decimals of the mint
+            let mut balance = total_balance(&accounts, accounts[0].amount.decimals)?;
+            if program_id == spl_token_2022::id() {
+                let mint_address = Pubkey::from_str(&contract)
+                    .map_err(|err| {
+                        log::error!("Error while creating the Pubkey object from mint address {}: {}", contract, err);
+                        ResponseError::CreatePubkeyError(Json(Code{code: "Error while creating the Pubkey object from mint address".to_string()}))
+                    })?;
+                let mint = get_mint(rpc_pool, &mint_address, commitment).await?;
+                apply_interest(&mut accounts, &mint);
+                balance = mint.amount(balance.amount);
+            }
+            tokens.push(TokenHolding {
+                contract,
+                program_id: program_id.to_string(),
+                balance,
+                accounts
+            });
+        }
+    }
+
+    Ok(Json(Portfolio { address: address.to_string(), balance, tokens, commitment }))
+}
+
+/// Maximum number of signatures returned by getSignaturesForAddress
+const MAX_SIGNATURES_LIMIT: usize = 1000;
+const DEFAULT_SIGNATURES_LIMIT: usize = 100;
+
+fn parse_signature(signature: &str) -> Result<Signature, ResponseError> {
+    Signature::from_str(signature)
+        .map_err(|err| {
+            log::error!("Failed during converting {} to Signature: {}", signature, err);
+            ResponseError::StrToSignatureError (Json(Code{ code: "Failed during parsing signature".to_string() }))
+        })
+}
+
+#[get("/address/<address>/transactions?<query..>")]
+pub async fn get_wallet_transactions(
+    address: &str,
+    query: AddressTransactionsQuery,
+    rpc_pool: &State<Arc<RpcPool>>,
+    config: &State<Config>,
+    version: ApiVersion
+) -> Result<Json<AddressTransactions>, ResponseError> {
+    let commitment = query.commitment.unwrap_or(config.commitment).for_blocks();
+
+    let pubkey = Pubkey::from_str(address)
+        .map_err(|err| {
+            log::error!("Error while creating the Pubkey object from owner address: {}", err);
+            ResponseError::CreatePubkeyError(Json(Code{code: "Error while creating the Pubkey object from owner address".to_string()}))
+        })?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_SIGNATURES_LIMIT);
+    if limit == 0 || limit > MAX_SIGNATURES_LIMIT {
+        return Err(ResponseError::InvalidPaginationError(Json(Code{ code: format!("Limit has to be between 1 and {}", MAX_SIGNATURES_LIMIT) })));
+    }
+
+    let before = query.before.as_deref().map(parse_signature).transpose()?;
+    let until = query.until.as_deref().map(parse_signature).transpose()?;
+
+    let signatures = rpc_pool.call(|rpc_client| rpc_client.get_signatures_for_address_with_config(&pubkey, GetConfirmedSignaturesForAddress2Config {
+            before,
+            until,
+            limit: Some(limit),
+            commitment: Some(commitment.to_commitment_config())
+        })).await
+        .map_err(|err| {
+            log::error!("Failed during getting the signatures of the address: {}", err);
+            ResponseError::GetSignaturesError(Json(Code{ code: "Failed during getting the signatures of the address".to_string() }))
+        })?;
+
+    // A full page means older transactions may remain, they are fetched with the last signature as cursor
+    let next_before = if signatures.len() == limit {
+        signatures.last().map(|signature| signature.signature.clone())
+    } else {
+        None
+    };
+
+    let rpc_pool = rpc_pool.inner();
+    let transactions: Vec<AddressTransaction> = stream::iter(signatures)
+        .map(|signature| async move {
+            let transaction = if query.include_transactions {
+                let transaction = get_transaction_info(rpc_pool, &parse_signature(&signature.signature)?, commitment).await?;
+                Some(transaction.versioned(version))
+            } else {
+                None
+            };
+
+            Ok::<AddressTransaction, ResponseError>(AddressTransaction {
+                status: if signature.err.is_none() { "Success".to_string() } else { "Failed".to_string() },
+                hash: signature.signature,
+                slot: signature.slot,
+                block_time: signature.block_time,
+                confirmation_status: signature.confirmation_status.map(Commitment::from),
+                transaction
+            })
+        })
+        .buffered(config.block_scan_concurrency)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(AddressTransactions {
+        address: address.to_string(),
+        transactions,
+        next_before,
+        commitment
+    }))
+}
+
+/// The key is generated inside the signer backend, only the insecure mode hands out the private key
+#[post("/address", data = "<wallet_parameters>")]
+pub async fn create_wallet_address(
+    wallet_parameters: Option<Json<CreateWalletRequest>>,
+    signer: &State<Arc<dyn SignerBackend>>,
+    config: &State<Config>
+) -> Result<Json<WalletResponse>, ResponseError> {
+    if config.insecure_private_keys {
+        let keypair = Keypair::new();
+        let byte_array = keypair.to_bytes();
+        let key_length = 32;
+        let address = bs58::encode(&byte_array[key_length..]).into_string();
+        let private_key = bs58::encode(&byte_array[0..key_length]).into_string();
+
+        return Ok(Json(WalletResponse { address, label: None, key_id: None, private_key: Some(private_key) }));
+    }
+
+    let label = wallet_parameters.and_then(|wallet_parameters| wallet_parameters.into_inner().label);
+    let key = signer.generate_key(label).await?;
+    Ok(Json(WalletResponse { address: key.address, label: key.label, key_id: Some(key.key_id), private_key: None }))
+}
+
+#[get("/keys?<archived>")]
+pub async fn list_keys(
+    signer: &State<Arc<dyn SignerBackend>>,
+    archived: Option<bool>
+) -> Result<Json<KeyList>, ResponseError> {
+    let keys = signer.list_keys(archived.unwrap_or(false)).await?;
+    Ok(Json(KeyList { keys }))
+}
 
-    let response = WalletResponse { address, private_key };
+#[patch("/keys/<key_id>", data = "<key_parameters>")]
+pub async fn update_key(
+    key_parameters: Json<UpdateKeyRequest>,
+    signer: &State<Arc<dyn SignerBackend>>,
+    key_id: &str
+) -> Result<Json<KeyInfo>, ResponseError> {
+    signer.update_key(key_id, &key_parameters).await.map(Json)
+}
+
+/// The seed stays in the signer backend, a generated mnemonic is only returned in insecure mode.
+/// Mnemonics are not generated when neither the backend nor the client would keep them.
+#[post("/hd-wallets", data = "<wallet_parameters>")]
+pub async fn create_hd_wallet(
+    wallet_parameters: Option<Json<CreateHdWalletRequest>>,
+    signer: &State<Arc<dyn SignerBackend>>,
+    config: &State<Config>
+) -> Result<Json<HdWalletInfo>, ResponseError> {
+    let wallet_parameters = wallet_parameters.map(Json::into_inner).unwrap_or_default();
+
+    let (mnemonic, generated) = match wallet_parameters.mnemonic {
+        Some(phrase) => {
+            let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+            let mnemonic = Mnemonic::from_phrase(&phrase, Language::English)
+                .map_err(|err| {
+                    log::error!("Error while parsing the mnemonic: {}", err);
+                    ResponseError::InvalidMnemonicError(Json(Code{ code: "Mnemonic is not a valid English BIP39 mnemonic".to_string() }))
+                })?;
+            (mnemonic, false)
+        },
+        None if signer.persists_keys() || config.insecure_private_keys => (Mnemonic::new(MnemonicType::Words24, Language::English), true),
+        None => {
+            log::error!("Error while creating the HD wallet: the signer backend does not persist generated seeds");
+            return Err(ResponseError::MnemonicRequiredError(Json(Code{ code: "Signer backend loses generated seeds on restart, import a mnemonic generated by the client".to_string() })));
+        }
+    };
+
+    let seed = generate_seed_from_seed_phrase_and_passphrase(mnemonic.phrase(), wallet_parameters.passphrase.as_deref().unwrap_or_default());
+    let mut wallet = signer.import_seed(&seed, wallet_parameters.label).await?;
+    if generated && config.insecure_private_keys {
+        wallet.mnemonic = Some(mnemonic.phrase().to_string());
+    }
+    Ok(Json(wallet))
+}
 
-    Json(response)
+#[post("/hd-wallets/<wallet_id>/addresses", data = "<address_parameters>")]
+pub async fn derive_hd_wallet_address(
+    address_parameters: Option<Json<DeriveAddressRequest>>,
+    signer: &State<Arc<dyn SignerBackend>>,
+    wallet_id: &str
+) -> Result<Json<KeyInfo>, ResponseError> {
+    let address_parameters = address_parameters.map(Json::into_inner).unwrap_or_default();
+    signer.derive_key(wallet_id, address_parameters.index, address_parameters.label).await.map(Json)
 }
\ No newline at end of file
@@ -23,6 +23,24 @@ pub async fn start_mock_server() -> MockServer {
         .mount(&mock_server)
         .await;
 
+    let get_health_req_expected_body = json!({
+        "method": "getHealth"
+    });
+
+    let get_health_resp_expected_body = json!({
+        "jsonrpc": "2.0",
+        "result": "ok",
+        "id": 1
+    });
+
+    let get_health_response = ResponseTemplate::new(200).set_body_json(get_health_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_health_req_expected_body))
+        .respond_with(get_health_response)
+        .mount(&mock_server)
+        .await;
+
     let get_version_req_expected_body = json!({
         "method": "getVersion"
     });
@@ -110,6 +128,147 @@ pub async fn start_mock_server() -> MockServer {
         .mount(&mock_server)
         .await;
 
+    // Block with a token transfer between the associated token accounts of two wallets, matched before the generic block above
+    let get_transfer_block_req_expected_body = json!({
+        "method": "getBlock",
+        "params": [234381070]
+    });
+
+    let get_transfer_block_resp_expected_body = json!({
+        "jsonrpc": "2.0",
+        "result": {
+          "blockHeight": 433,
+          "blockTime": 1690000000,
+          "blockhash": "5HGWYu788RuwwKLBkwXsiRdxMHbRSWqXvvPCdkxC6Cn3",
+          "parentSlot": 234381069,
+          "previousBlockhash": "8qGKi2ycYh7xF17VtcSxd6rbrnWLXotNFWJ6gMCLyeY3",
+          "transactions": [
+            {
+              "meta": {
+                "err": null,
+                "fee": 5000,
+                "innerInstructions": [],
+                "logMessages": [],
+                "postBalances": [2000000000, 2039280, 2039280, 1],
+                "postTokenBalances": [
+                  {
+                    "accountIndex": 1,
+                    "mint": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+                    "owner": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "uiTokenAmount": {
+                      "amount": "0",
+                      "decimals": 6,
+                      "uiAmount": 0.0,
+                      "uiAmountString": "0"
+                    }
+                  },
+                  {
+                    "accountIndex": 2,
+                    "mint": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+                    "owner": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH",
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "uiTokenAmount": {
+                      "amount": "5000000",
+                      "decimals": 6,
+                      "uiAmount": 5.0,
+                      "uiAmountString": "5"
+                    }
+                  }
+                ],
+                "preBalances": [2000005000, 2039280, 2039280, 1],
+                "preTokenBalances": [
+                  {
+                    "accountIndex": 1,
+                    "mint": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+                    "owner": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "uiTokenAmount": {
+                      "amount": "5000000",
+                      "decimals": 6,
+                      "uiAmount": 5.0,
+                      "uiAmountString": "5"
+                    }
+                  },
+                  {
+                    "accountIndex": 2,
+                    "mint": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+                    "owner": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH",
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "uiTokenAmount": {
+                      "amount": "0",
+                      "decimals": 6,
+                      "uiAmount": 0.0,
+                      "uiAmountString": "0"
+                    }
+                  }
+                ],
+                "rewards": [],
+                "status": {
+                  "Ok": null
+                }
+              },
+              "transaction": {
+                "message": {
+                  "accountKeys": [
+                    "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+                    "2Ux2czV1TUwW7EmweeyKPj6noLLArtarzFftqKpntMae",
+                    "Cwaw9f114aXGpm2RGiP7HrFYTL1ERLyZu21iDc1CSFZY",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                  ],
+                  "header": {
+                    "numReadonlySignedAccounts": 0,
+                    "numReadonlyUnsignedAccounts": 1,
+                    "numRequiredSignatures": 1
+                  },
+                  "instructions": [
+                    {
+                      "accounts": [1, 2, 0],
+                      "data": "3QDJ9TwUE2Dm",
+                      "programIdIndex": 3
+                    }
+                  ],
+                  "recentBlockhash": "8qGKi2ycYh7xF17VtcSxd6rbrnWLXotNFWJ6gMCLyeY3"
+                },
+                "signatures": [
+                  "FoRFKuuynY6eJF8MgfYqGJgTmyGidsKWvNaPVEqQR9Epv3JY2LBqDTeb3yEQCWgmEsqm5JeuZ5SSf4jLqab7aBs"
+                ]
+              }
+            }
+          ]
+        },
+        "id": 1
+      });
+
+    let get_transfer_block_response = ResponseTemplate::new(200).set_body_json(get_transfer_block_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_transfer_block_req_expected_body))
+        .respond_with(get_transfer_block_response)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+
+    for blocks_method in ["getBlocks", "getBlocksWithLimit"] {
+        let get_blocks_req_expected_body = json!({
+            "method": blocks_method
+        });
+
+        let get_blocks_resp_expected_body = json!({
+            "jsonrpc": "2.0",
+            "result": [234381063, 234381065],
+            "id": 1
+        });
+
+        let get_blocks_response = ResponseTemplate::new(200).set_body_json(get_blocks_resp_expected_body);
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(get_blocks_req_expected_body))
+            .respond_with(get_blocks_response)
+            .mount(&mock_server)
+            .await;
+    }
+
     let get_block_height_req_expected_body = json!({
         "method": "getBlockHeight"
     });
@@ -128,6 +287,130 @@ pub async fn start_mock_server() -> MockServer {
         .mount(&mock_server)
         .await;
 
+    // Accounts are unknown unless mocked otherwise
+    let get_account_info_req_expected_body = json!({
+        "method": "getAccountInfo"
+    });
+
+    let get_account_info_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": {
+        "context": {
+          "slot": 234381065
+        },
+        "value": null
+      },
+      "id": 1
+    });
+
+    let get_account_info_response = ResponseTemplate::new(200).set_body_json(get_account_info_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_account_info_req_expected_body))
+        .respond_with(get_account_info_response)
+        .mount(&mock_server)
+        .await;
+
+    // USDX is a mint of the SPL Token program, the other one a Token-2022 mint with a 0.5% transfer fee capped at 5000
+    for (mint_address, program_id, mint_data, space) in [
+        ("usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs", "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAOh2SBcAAAAGAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==", 82),
+        ("2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo", "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAOh2SBcAAAAGAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQEAbAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAIgTAAAAAAAAMgAAAAAAAAAAAIgTAAAAAAAAMgA=", 278),
+    ] {
+        let get_mint_req_expected_body = json!({
+            "method": "getAccountInfo",
+            "params": [mint_address]
+        });
+
+        let get_mint_resp_expected_body = json!({
+          "jsonrpc": "2.0",
+          "result": {
+            "context": {
+              "slot": 234381065
+            },
+            "value": {
+              "data": [mint_data, "base64"],
+              "executable": false,
+              "lamports": 1461600,
+              "owner": program_id,
+              "rentEpoch": 0,
+              "space": space
+            }
+          },
+          "id": 1
+        });
+
+        let get_mint_response = ResponseTemplate::new(200).set_body_json(get_mint_resp_expected_body);
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(get_mint_req_expected_body))
+            .respond_with(get_mint_response)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let get_epoch_info_req_expected_body = json!({
+        "method": "getEpochInfo"
+    });
+
+    let get_epoch_info_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": {
+        "absoluteSlot": 234381065,
+        "blockHeight": 1233,
+        "epoch": 542,
+        "slotIndex": 237065,
+        "slotsInEpoch": 432000,
+        "transactionCount": null
+      },
+      "id": 1
+    });
+
+    let get_epoch_info_response = ResponseTemplate::new(200).set_body_json(get_epoch_info_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_epoch_info_req_expected_body))
+        .respond_with(get_epoch_info_response)
+        .mount(&mock_server)
+        .await;
+
+    let get_signatures_for_address_req_expected_body = json!({
+        "method": "getSignaturesForAddress"
+    });
+
+    let get_signatures_for_address_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": [
+        {
+          "blockTime": 1690000000,
+          "confirmationStatus": "finalized",
+          "err": null,
+          "memo": null,
+          "signature": "4fXvvk1kZiuBjz4J7AcVJF4QaL2pPRDBHeqKrWvMsq3L6hUT7xZXPefDdNuARk2bkpaQGRn2LSJjMixm62ecmf8b",
+          "slot": 234381070
+        },
+        {
+          "blockTime": 1690000000,
+          "confirmationStatus": "finalized",
+          "err": {
+            "InstructionError": [0, {"Custom": 1}]
+          },
+          "memo": null,
+          "signature": "2Ana1pUpv2ZbMVkwF5FXapYeBEjdxDatLn7nvJkhgTSXbs59SyZSx866bXirPgj8QQVB57uxHJBG1YFvkRbFj4T",
+          "slot": 234381070
+        }
+      ],
+      "id": 1
+    });
+
+    let get_signatures_for_address_response = ResponseTemplate::new(200).set_body_json(get_signatures_for_address_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_signatures_for_address_req_expected_body))
+        .respond_with(get_signatures_for_address_response)
+        .mount(&mock_server)
+        .await;
+
     let get_balance_req_expected_body = json!({
         "method": "getBalance"
     });
@@ -207,6 +490,82 @@ pub async fn start_mock_server() -> MockServer {
         .respond_with(get_fee_for_message_response)
         .mount(&mock_server)
         .await;
+
+    let get_recent_prioritization_fees_req_expected_body = json!({
+      "method": "getRecentPrioritizationFees"
+    });
+
+    let get_recent_prioritization_fees_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": [
+        { "slot": 234381061, "prioritizationFee": 0 },
+        { "slot": 234381062, "prioritizationFee": 1000 },
+        { "slot": 234381063, "prioritizationFee": 20000 },
+        { "slot": 234381064, "prioritizationFee": 3000 },
+        { "slot": 234381065, "prioritizationFee": 5000 }
+      ],
+      "id": 1
+    });
+
+    let get_recent_prioritization_fees_response = ResponseTemplate::new(200).set_body_json(get_recent_prioritization_fees_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_recent_prioritization_fees_req_expected_body))
+        .respond_with(get_recent_prioritization_fees_response)
+        .mount(&mock_server)
+        .await;
+
+    let simulate_transaction_req_expected_body = json!({
+      "method": "simulateTransaction"
+    });
+
+    let simulate_transaction_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": {
+        "context": { "slot": 234381065 },
+        "value": {
+          "err": null,
+          "accounts": null,
+          "logs": [
+            "Program 11111111111111111111111111111111 invoke [1]",
+            "Program 11111111111111111111111111111111 success"
+          ],
+          "returnData": null,
+          "unitsConsumed": 450
+        }
+      },
+      "id": 1
+    });
+
+    let simulate_transaction_response = ResponseTemplate::new(200).set_body_json(simulate_transaction_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(simulate_transaction_req_expected_body))
+        .respond_with(simulate_transaction_response)
+        .mount(&mock_server)
+        .await;
+
+    // Rent exemption of a SPL Token account and of a Token-2022 account with a transfer fee
+    for (space, lamports) in [(165, 2039280), (182, 2157600)] {
+        let get_minimum_balance_req_expected_body = json!({
+          "method": "getMinimumBalanceForRentExemption",
+          "params": [space]
+        });
+
+        let get_minimum_balance_resp_expected_body = json!({
+          "jsonrpc": "2.0",
+          "result": lamports,
+          "id": 1
+        });
+
+        let get_minimum_balance_response = ResponseTemplate::new(200).set_body_json(get_minimum_balance_resp_expected_body);
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(get_minimum_balance_req_expected_body))
+            .respond_with(get_minimum_balance_response)
+            .mount(&mock_server)
+            .await;
+    }
     
     let get_transaction_req_expected_body = json!({
       "method": "getTransaction",
@@ -269,6 +628,224 @@ pub async fn start_mock_server() -> MockServer {
         .mount(&mock_server)
         .await;
 
+    let get_v0_transaction_req_expected_body = json!({
+        "method": "getTransaction",
+        "params": ["4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9"]
+    });
+
+    let get_v0_transaction_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": {
+        "meta": {
+          "err": null,
+          "fee": 5000,
+          "innerInstructions": [],
+          "loadedAddresses": {
+            "readonly": ["11111111111111111111111111111111"],
+            "writable": ["22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "Cwaw9f114aXGpm2RGiP7HrFYTL1ERLyZu21iDc1CSFZY"]
+          },
+          "logMessages": [],
+          "postBalances": [1000000000, 1, 1000000000, 2039280, 1],
+          "postTokenBalances": [
+            {
+              "accountIndex": 3,
+              "mint": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+              "owner": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH",
+              "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+              "uiTokenAmount": {
+                "amount": "5000000",
+                "decimals": 6,
+                "uiAmount": 5.0,
+                "uiAmountString": "5"
+              }
+            }
+          ],
+          "preBalances": [2000005000, 1, 0, 2039280, 1],
+          "preTokenBalances": [],
+          "rewards": [],
+          "status": {
+            "Ok": null
+          }
+        },
+        "slot": 234381070,
+        "transaction": {
+          "message": {
+            "accountKeys": [
+              "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+              "AddressLookupTab1e1111111111111111111111111"
+            ],
+            "addressTableLookups": [
+              {
+                "accountKey": "3wyAj7Rt1TWVPZVteFJPLa26JmLvdb1CAKEFZm3NY75E",
+                "readonlyIndexes": [1],
+                "writableIndexes": [0, 2]
+              }
+            ],
+            "header": {
+              "numReadonlySignedAccounts": 0,
+              "numReadonlyUnsignedAccounts": 1,
+              "numRequiredSignatures": 1
+            },
+            "instructions": [
+              {
+                "accounts": [0, 2],
+                "data": "3Bxs4h24hBtQy9rw",
+                "programIdIndex": 4
+              }
+            ],
+            "recentBlockhash": "8qGKi2ycYh7xF17VtcSxd6rbrnWLXotNFWJ6gMCLyeY3"
+          },
+          "signatures": [
+            "4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9"
+          ]
+        },
+        "version": 0
+      },
+      "blockTime": 1690000000,
+      "id": 1
+    });
+
+    let get_v0_transaction_response = ResponseTemplate::new(200).set_body_json(get_v0_transaction_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_v0_transaction_req_expected_body))
+        .respond_with(get_v0_transaction_response)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+
+    let get_binary_transaction_req_expected_body = json!({
+        "method": "getTransaction",
+        "params": ["4fXvvk1kZiuBjz4J7AcVJF4QaL2pPRDBHeqKrWvMsq3L6hUT7xZXPefDdNuARk2bkpaQGRn2LSJjMixm62ecmf8b"]
+    });
+
+    let get_binary_transaction_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": {
+        "meta": {
+          "err": null,
+          "fee": 5000,
+          "innerInstructions": [],
+          "logMessages": [],
+          "postBalances": [1000000000, 1000000000, 1],
+          "postTokenBalances": [],
+          "preBalances": [2000005000, 0, 1],
+          "preTokenBalances": [],
+          "rewards": [],
+          "status": {
+            "Ok": null
+          }
+        },
+        "slot": 234381070,
+        "transaction": [
+          "AbdH1x1A6wAmqUa8mN/lj2RoLvLkV2IZ+EA59zOaHDymxcZbcpVWTIgfZgErdXLBKobO/JsPlOiJrWOxG2XSHgABAAEDlV8aVrjcIB+zmcGT15JVCWB726TDqmvpsp5jXvk0u88PNTwkAU6H0ZBYABlIHA9Mqn4mGstHLIQmthBySR6R/AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAdF8DOBS3nOqKTz9asg3g1hX0OhYBNuLzTIqHq7cDdYwBAgIAAQwCAAAAAMqaOwAAAAA=",
+          "base64"
+        ]
+      },
+      "blockTime": 1690000000,
+      "id": 1
+    });
+
+    let get_binary_transaction_response = ResponseTemplate::new(200).set_body_json(get_binary_transaction_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_binary_transaction_req_expected_body))
+        .respond_with(get_binary_transaction_response)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+
+    let get_parsed_transaction_req_expected_body = json!({
+        "method": "getTransaction",
+        "params": ["2Ana1pUpv2ZbMVkwF5FXapYeBEjdxDatLn7nvJkhgTSXbs59SyZSx866bXirPgj8QQVB57uxHJBG1YFvkRbFj4T"]
+    });
+
+    let get_parsed_transaction_resp_expected_body = json!({
+      "jsonrpc": "2.0",
+      "result": {
+        "meta": {
+          "err": null,
+          "fee": 5000,
+          "innerInstructions": [],
+          "loadedAddresses": {
+            "readonly": [],
+            "writable": ["22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH"]
+          },
+          "logMessages": [],
+          "postBalances": [1000000000, 1, 1000000000],
+          "postTokenBalances": [],
+          "preBalances": [2000005000, 1, 0],
+          "preTokenBalances": [],
+          "rewards": [],
+          "status": {
+            "Ok": null
+          }
+        },
+        "slot": 234381070,
+        "transaction": {
+          "message": {
+            "accountKeys": [
+              {
+                "pubkey": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+                "signer": true,
+                "source": "transaction",
+                "writable": true
+              },
+              {
+                "pubkey": "11111111111111111111111111111111",
+                "signer": false,
+                "source": "transaction",
+                "writable": false
+              },
+              {
+                "pubkey": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH",
+                "signer": false,
+                "source": "lookupTable",
+                "writable": true
+              }
+            ],
+            "addressTableLookups": [
+              {
+                "accountKey": "3wyAj7Rt1TWVPZVteFJPLa26JmLvdb1CAKEFZm3NY75E",
+                "readonlyIndexes": [],
+                "writableIndexes": [0]
+              }
+            ],
+            "instructions": [
+              {
+                "parsed": {
+                  "info": {
+                    "destination": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH",
+                    "lamports": 1000000000,
+                    "source": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN"
+                  },
+                  "type": "transfer"
+                },
+                "program": "system",
+                "programId": "11111111111111111111111111111111"
+              }
+            ],
+            "recentBlockhash": "8qGKi2ycYh7xF17VtcSxd6rbrnWLXotNFWJ6gMCLyeY3"
+          },
+          "signatures": [
+            "2Ana1pUpv2ZbMVkwF5FXapYeBEjdxDatLn7nvJkhgTSXbs59SyZSx866bXirPgj8QQVB57uxHJBG1YFvkRbFj4T"
+          ]
+        },
+        "version": 0
+      },
+      "blockTime": 1690000000,
+      "id": 1
+    });
+
+    let get_parsed_transaction_response = ResponseTemplate::new(200).set_body_json(get_parsed_transaction_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_parsed_transaction_req_expected_body))
+        .respond_with(get_parsed_transaction_response)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+
     let get_signature_statuses_req_expected_body = json!({
         "method": "getSignatureStatuses"
     });
@@ -389,5 +966,171 @@ pub async fn start_mock_server() -> MockServer {
         .mount(&mock_server)
         .await;
 
+
+    let get_token_2022_accounts_resp_expected_body = json!({
+        "jsonrpc": "2.0",
+        "result": {
+          "context": {
+            "slot": 1114
+          },
+          "value": [
+            {
+              "account": {
+                "data": {
+                  "program": "spl-token-2022",
+                  "parsed": {
+                    "info": {
+                      "extensions": [
+                        {
+                          "extension": "transferFeeAmount",
+                          "state": {
+                            "withheldAmount": 500
+                          }
+                        }
+                      ],
+                      "isNative": false,
+                      "mint": "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo",
+                      "owner": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+                      "state": "initialized",
+                      "tokenAmount": {
+                        "amount": "123456789012345678",
+                        "decimals": 6,
+                        "uiAmount": 123456789012.34568,
+                        "uiAmountString": "123456789012.345678"
+                      }
+                    },
+                    "type": "account"
+                  },
+                  "space": 178
+                },
+                "executable": false,
+                "lamports": 2130000,
+                "owner": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+                "rentEpoch": 0,
+                "space": 178
+              },
+              "pubkey": "7UX2i7SucgLMQcfZ75s3VXmZZY4YRUyJN9X1RgfMoDUi"
+            },
+            {
+              "account": {
+                "data": {
+                  "program": "spl-token-2022",
+                  "parsed": {
+                    "info": {
+                      "delegate": "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T",
+                      "delegatedAmount": {
+                        "amount": "500000",
+                        "decimals": 6,
+                        "uiAmount": 0.5,
+                        "uiAmountString": "0.5"
+                      },
+                      "extensions": [
+                        {
+                          "extension": "transferFeeAmount",
+                          "state": {
+                            "withheldAmount": 0
+                          }
+                        }
+                      ],
+                      "isNative": false,
+                      "mint": "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo",
+                      "owner": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+                      "state": "frozen",
+                      "tokenAmount": {
+                        "amount": "1000000",
+                        "decimals": 6,
+                        "uiAmount": 1.0,
+                        "uiAmountString": "1"
+                      }
+                    },
+                    "type": "account"
+                  },
+                  "space": 178
+                },
+                "executable": false,
+                "lamports": 2130000,
+                "owner": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+                "rentEpoch": 0,
+                "space": 178
+              },
+              "pubkey": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"
+            }
+          ]
+        },
+        "id": 1
+    });
+
+    // The wallet only holds the Token-2022 mint, either by mint or by program
+    for token_accounts_filter in [json!({"mint": "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo"}), json!({"programId": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"})] {
+        let get_token_2022_accounts_req_expected_body = json!({
+            "method": "getTokenAccountsByOwner",
+            "params": ["B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", token_accounts_filter]
+        });
+
+        let get_token_2022_accounts_response = ResponseTemplate::new(200).set_body_json(get_token_2022_accounts_resp_expected_body.clone());
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(get_token_2022_accounts_req_expected_body))
+            .respond_with(get_token_2022_accounts_response)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+    }
+
+    let get_spl_token_accounts_req_expected_body = json!({
+        "method": "getTokenAccountsByOwner",
+        "params": ["B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", {"programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"}]
+    });
+
+    let get_spl_token_accounts_resp_expected_body = json!({
+        "jsonrpc": "2.0",
+        "result": {
+          "context": {
+            "slot": 1114
+          },
+          "value": [
+            {
+              "account": {
+                "data": {
+                  "program": "spl-token",
+                  "parsed": {
+                    "info": {
+                      "isNative": false,
+                      "mint": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+                      "owner": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+                      "state": "initialized",
+                      "tokenAmount": {
+                        "amount": "5000000",
+                        "decimals": 6,
+                        "uiAmount": 5.0,
+                        "uiAmountString": "5"
+                      }
+                    },
+                    "type": "account"
+                  },
+                  "space": 165
+                },
+                "executable": false,
+                "lamports": 2039280,
+                "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "rentEpoch": 0,
+                "space": 165
+              },
+              "pubkey": "C2gJg6tKpQs41PRS1nC8aw3ZKNZK3HQQZGVrDFDup5nx"
+            }
+          ]
+        },
+        "id": 1
+    });
+
+    let get_spl_token_accounts_response = ResponseTemplate::new(200).set_body_json(get_spl_token_accounts_resp_expected_body);
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(get_spl_token_accounts_req_expected_body))
+        .respond_with(get_spl_token_accounts_response)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+
     mock_server
 }
\ No newline at end of file
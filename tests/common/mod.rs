@@ -1,15 +1,57 @@
 use rocket::{Ignite, Rocket};
-use blockchain_solana::server;
-use solana_client::rpc_client::RpcClient;
+use blockchain_solana::{config::{Cluster, Config}, pool::RpcPool, server, signers::{self, InMemorySigner, SignerBackend}};
+use solana_sdk::signature::Keypair;
 use std::sync::Arc;
+use wiremock::Mock;
 
 mod mock;
 
 pub async fn setup() -> Rocket<Ignite> {
+    setup_with(|_| {}).await
+}
+
+/// Starts the server with the mock server as primary endpoint, letting the test adjust the configuration
+pub async fn setup_with(configure: impl FnOnce(&mut Config)) -> Rocket<Ignite> {
+    start(configure, |config| signers::from_config(config).unwrap(), Vec::new()).await
+}
+
+/// Starts the server with the test key, the given RPC mocks take precedence over the default ones
+pub async fn setup_with_rpc_mocks(mocks: Vec<Mock>) -> Rocket<Ignite> {
+    start(|_| {}, test_key_signer, mocks).await
+}
+
+/// Starts the server with the wallet B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN in the in-memory signer
+pub async fn setup_with_test_key() -> Rocket<Ignite> {
+    start(|_| {}, test_key_signer, Vec::new()).await
+}
+
+fn test_key_signer(_: &Config) -> Arc<dyn SignerBackend> {
+    let signer = InMemorySigner::default();
+    signer.insert(test_keypair(), None);
+    Arc::new(signer)
+}
+
+pub fn test_keypair() -> Keypair {
+    let mut bytes = bs58::decode("3J5zuqwqdecmVY7Xvk5T9j4ks1LTYAiq7mxsenuXGaZH").into_vec().unwrap();
+    bytes.append(&mut bs58::decode("B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN").into_vec().unwrap());
+    Keypair::from_bytes(&bytes).unwrap()
+}
+
+async fn start(configure: impl FnOnce(&mut Config), signer: impl FnOnce(&Config) -> Arc<dyn SignerBackend>, mocks: Vec<Mock>) -> Rocket<Ignite> {
     let mock_server = mock::start_mock_server().await;
-    
-    let rpc_url = mock_server.uri();
-    let rpc_client = Arc::new(RpcClient::new(rpc_url));
+    for rpc_mock in mocks {
+        rpc_mock.with_priority(1).mount(&mock_server).await;
+    }
+
+    let mut config = Config {
+        cluster: Cluster::Custom,
+        rpc_url: Some(mock_server.uri()),
+        ..Config::default()
+    };
+    configure(&mut config);
+    config.validate().unwrap();
+    let rpc_pool = Arc::new(RpcPool::new(&config));
+    let signer = signer(&config);
 
-    server::start_server(rpc_client).await.unwrap()
+    server::start_server(rpc_pool, signer, config).await.unwrap()
 }
\ No newline at end of file
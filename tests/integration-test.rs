@@ -1,6 +1,11 @@
 use std::env;
-use rocket::{http::{Status, ContentType}, local::asynchronous::Client};
-use blockchain_solana::models::{SignTransactionRequest, AccountInfo, SendTransactionRequest};
+use rocket::{http::{Status, ContentType, Header}, local::asynchronous::Client};
+use blockchain_solana::{config::{Cluster, Config, SignerKind}, errors::ResponseError, models::{Amount, ComputeBudget, SignTransactionRequest, AccountInfo, SendTransactionRequest}, pool::RpcPool, signers::{KeystoreSigner, SignerBackend}};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use solana_sdk::{compute_budget::{self, ComputeBudgetInstruction}, pubkey::Pubkey, signature::{generate_seed_from_seed_phrase_and_passphrase, Keypair}, signer::Signer, transaction::Transaction};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate, matchers::{body_partial_json, method, path}};
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
 use assert_json_diff::assert_json_eq;
 use serde_json::{json, Value};
 
@@ -26,7 +31,13 @@ async fn test_get_latest_block() {
     let expected_response = json!({
       "height":234381065,
       "hash":"8qGKi2ycYh7xF17VtcSxd6rbrnWLXotNFWJ6gMCLyeY3",
-      "transactions":[]
+      "transactions":[],
+      "commitment":"finalized",
+      "slot":234381065,
+      "blockHeight":428,
+      "parentSlot":429,
+      "blockTime":null,
+      "previousBlockhash":"mfcyqEXB3DnHXki6KjjmZck6YjmZLvpAByy2fj4nh6B"
     });
 
     assert_json_eq!(actual_response, expected_response);
@@ -55,11 +66,19 @@ async fn test_scan_block() {
       "transactions":[{
         "blockHash":"8qGKi2ycYh7xF17VtcSxd6rbrnWLXotNFWJ6gMCLyeY3",
         "blockHeight":234381065,
-        "fee":5e-6,
+        "slot":234381065,
+        "blockTime":null,
+        "fee":{"amount":"5000","decimals":9,"uiAmountString":"0.000005"},
         "from":[],
         "to":[],
         "hash":"2nBhEBYYvfaAe16UMNqRHre4YNSskvuYgx3M6E4JP1oDYvZEJHvoPzyUidNgNX5r9sTyN1J9UxtbCXy2rqYcuyuv",
-        "status":"Success"}]
+        "status":"Success"}],
+      "commitment": "finalized",
+      "slot": 234381065,
+      "blockHeight": 428,
+      "parentSlot": 429,
+      "blockTime": null,
+      "previousBlockhash": "mfcyqEXB3DnHXki6KjjmZck6YjmZLvpAByy2fj4nh6B"
     });
 
     assert_json_eq!(actual_response, expected_response);
@@ -85,11 +104,14 @@ async fn test_get_transaction_details() {
     let expected_response = json!({
       "blockHash":"8qGKi2ycYh7xF17VtcSxd6rbrnWLXotNFWJ6gMCLyeY3",
       "blockHeight":430,
-      "fee":5e-6,
+      "slot":430,
+      "blockTime":null,
+      "fee":{"amount":"5000","decimals":9,"uiAmountString":"0.000005"},
       "from":[],
       "to":[],
       "hash":"2nBhEBYYvfaAe16UMNqRHre4YNSskvuYgx3M6E4JP1oDYvZEJHvoPzyUidNgNX5r9sTyN1J9UxtbCXy2rqYcuyuv",
-      "status":"Success"
+      "status":"Success",
+      "commitment":"finalized"
     });
 
     assert_json_eq!(actual_response, expected_response);
@@ -113,7 +135,8 @@ async fn test_get_confirmation_count() {
     assert_eq!(actual_status, Status::Ok);
 
     let expected_response = json!({
-      "confirmationsCount": 805
+      "confirmationsCount": 805,
+      "commitment": "finalized"
     });
 
     assert_json_eq!(actual_response, expected_response);
@@ -153,6 +176,104 @@ async fn test_get_calculated_fee() {
     assert_json_eq!(actual_response_no_param, expected_response_no_param);
 }
 
+#[rocket::async_test]
+async fn test_get_calculated_fee_token() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8040");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    // SPL Token and Token-2022 mints
+    let response_token = client.get("/fee/estimate?contract=usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs").dispatch().await;
+    let response_token_2022 = client.get("/fee/estimate?contract=2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo").dispatch().await;
+
+    let actual_status_token = response_token.status();
+    let actual_response_token: Value = serde_json::from_str(response_token.into_string().await.unwrap().as_ref()).unwrap();
+
+    let actual_status_token_2022 = response_token_2022.status();
+    let actual_response_token_2022: Value = serde_json::from_str(response_token_2022.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status_token, Status::Ok);
+    assert_eq!(actual_status_token_2022, Status::Ok);
+    assert_json_eq!(actual_response_token, json!({"calculatedFee": 5000}));
+    assert_json_eq!(actual_response_token_2022, json!({"calculatedFee": 5000}));
+}
+
+#[rocket::async_test]
+async fn test_estimate_transfer_fee() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8033");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let sol_transfer = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "200000000", "decimals": 9}, "contract": null}],
+        "to": [
+            {"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "99999999", "decimals": 9}, "contract": null},
+            {"adress": "DAiB6ZGYa5xXuTMjsNVrS4PEnzutWB33HeQM6gmPJSZi", "amount": {"amount": "99999999", "decimals": 9}, "contract": null}
+        ]
+    });
+    let response = client.post("/fee/estimate").header(ContentType::JSON).body(sol_transfer.to_string()).dispatch().await;
+    let sol_status = response.status();
+    let sol_estimate: Value = response.into_json().await.unwrap();
+
+    // The recipients do not have token accounts yet, a SPL Token and a Token-2022 one are created
+    let token_transfer = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "1000", "decimals": 6}, "contract": null}],
+        "to": [
+            {"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 6}, "contract": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs"},
+            {"adress": "DAiB6ZGYa5xXuTMjsNVrS4PEnzutWB33HeQM6gmPJSZi", "amount": {"amount": "1000", "decimals": 6}, "contract": "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo"}
+        ],
+        "createAssociatedTokenAccount": true,
+        "computeUnitLimit": 100000,
+        "priorityFeeMicroLamports": "auto"
+    });
+    let response = client.post("/fee/estimate").header(ContentType::JSON).body(token_transfer.to_string()).dispatch().await;
+    let token_status = response.status();
+    let token_estimate: Value = response.into_json().await.unwrap();
+
+    let invalid_status = client.post("/fee/estimate").header(ContentType::JSON)
+        .body(json!({"from": sol_transfer["from"], "to": sol_transfer["to"], "computeUnitLimit": 0}).to_string())
+        .dispatch().await.status();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(sol_status, Status::Ok);
+    assert_json_eq!(sol_estimate, json!({
+        "baseFee": 5000,
+        "computeUnits": 450,
+        // Default limit of two instructions
+        "computeUnitLimit": 400000,
+        "computeUnitPrice": 0,
+        "priorityFee": 0,
+        "priorityFeePercentiles": [
+            {"percentile": 25, "computeUnitPrice": 1000, "priorityFee": 400},
+            {"percentile": 50, "computeUnitPrice": 3000, "priorityFee": 1200},
+            {"percentile": 75, "computeUnitPrice": 5000, "priorityFee": 2000},
+            {"percentile": 90, "computeUnitPrice": 20000, "priorityFee": 8000}
+        ],
+        "rent": 0,
+        "createdAccounts": [],
+        "total": 5000
+    }));
+
+    assert_eq!(token_status, Status::Ok);
+    let usdx_account = get_associated_token_address(&Pubkey::from_str("22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH").unwrap(), &Pubkey::from_str("usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs").unwrap());
+    let token_2022_account = get_associated_token_address_with_program_id(&Pubkey::from_str("DAiB6ZGYa5xXuTMjsNVrS4PEnzutWB33HeQM6gmPJSZi").unwrap(), &Pubkey::from_str("2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo").unwrap(), &spl_token_2022::id());
+    assert_eq!(token_estimate["createdAccounts"], json!([usdx_account.to_string(), token_2022_account.to_string()]));
+    assert_eq!(token_estimate["rent"], 2039280 + 2157600);
+    assert_eq!(token_estimate["computeUnitLimit"], 100000);
+    assert_eq!(token_estimate["computeUnitPrice"], 5000);
+    assert_eq!(token_estimate["priorityFee"], 500);
+    assert_eq!(token_estimate["total"], 5000 + 500 + 2039280 + 2157600);
+
+    assert_eq!(invalid_status, Status::BadRequest);
+}
+
 #[rocket::async_test]
 async fn test_address_generate() {
     dotenv::dotenv().ok();
@@ -164,11 +285,14 @@ async fn test_address_generate() {
     let response = client.post("/address").dispatch().await;
 
     let actual_status = response.status();
-    let _actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
     
     client.terminate().await.shutdown().await;
     
     assert_eq!(actual_status, Status::Ok);
+    // The key stays in the signer, it is referenced by the address
+    assert_eq!(actual_response["keyId"], actual_response["address"]);
+    assert!(actual_response.get("privateKey").is_none());
 }
 
 #[rocket::async_test]
@@ -194,11 +318,21 @@ async fn test_address_balance() {
     assert_eq!(actual_status_param, Status::Ok);
 
     let expected_response_no_param: Value = json!({
-        "balance":2.0,
+        "balance":{"amount":"2000000000","decimals":9,"uiAmountString":"2"},
+        "commitment":"finalized"
       });
     
     let expected_response_param: Value = json!({
-        "balance":0.1,
+        "balance":{"amount":"1","decimals":1,"uiAmountString":"0.1"},
+        "accounts":[
+            {
+                "address":"C2gJg6tKpQs41PRS1nC8aw3ZKNZK3HQQZGVrDFDup5nx",
+                "amount":{"amount":"1","decimals":1,"uiAmountString":"0.1"},
+                "frozen":false,
+                "delegate":"4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T"
+            }
+        ],
+        "commitment":"finalized"
     });
 
     assert_json_eq!(actual_response_no_param, expected_response_no_param);
@@ -210,28 +344,32 @@ async fn test_sign_transaction() {
     dotenv::dotenv().ok();
     env::set_var("ROCKET_PORT", "8008");
 
-    let rocket = common::setup().await;
+    let rocket = common::setup_with(|config| config.insecure_private_keys = true).await;
     let client = Client::tracked(rocket).await.unwrap();
 
     let request : SignTransactionRequest = SignTransactionRequest { from: [
         AccountInfo{
           adress: "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN".to_string(),
-          amount: 200000000 as f64,
-          contract: None
+          amount: Amount::new(200000000, 9),
+          contract: None,
+          owner: None
         }
       ].into(), to: [
         AccountInfo{
           adress: "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH".to_string(),
-          amount: 99999999 as f64,
-          contract: None
+          amount: Amount::new(99999999, 9),
+          contract: None,
+          owner: None
         }
         ,
         AccountInfo{
           adress: "DAiB6ZGYa5xXuTMjsNVrS4PEnzutWB33HeQM6gmPJSZi".to_string(),
-          amount: 99999999 as f64,
-          contract: None
+          amount: Amount::new(99999999, 9),
+          contract: None,
+          owner: None
         }
-      ].into(), private_key: "3J5zuqwqdecmVY7Xvk5T9j4ks1LTYAiq7mxsenuXGaZH".to_string() };
+      ].into(), key_id: None, private_key: Some("3J5zuqwqdecmVY7Xvk5T9j4ks1LTYAiq7mxsenuXGaZH".to_string()), create_associated_token_account: false,
+      fee_payer: None, key_ids: HashMap::new(), partial: false, compute_budget: ComputeBudget::default() };
 
     let json_payload = rocket::serde::json::to_string(&request).unwrap();
     let response = client.post("/transactions/sign").header(ContentType::JSON).body(json_payload).dispatch().await;
@@ -251,6 +389,9 @@ async fn test_sign_transaction() {
     assert_json_eq!(actual_response, expected_response);
 }
 
+/// Transfer signed by the wallet of the test key, the sendTransaction mock returns its signature
+const SIGNED_TRANSACTION: &str = "{\"signatures\":[[1],[192,121,121,143,216,202,102,196,11,13,214,44,86,244,245,201,28,233,91,194,78,106,95,42,171,37,195,24,63,33,93,82,230,248,1,203,171,207,190,0,71,82,19,206,53,211,198,27,89,6,220,46,116,17,142,154,62,226,182,118,134,10,211,10]],\"message\":{\"header\":{\"numRequiredSignatures\":1,\"numReadonlySignedAccounts\":0,\"numReadonlyUnsignedAccounts\":1},\"accountKeys\":[[4],[15,53,60,36,1,78,135,209,144,88,0,25,72,28,15,76,170,126,38,26,203,71,44,132,38,182,16,114,73,30,145,252],[149,95,26,86,184,220,32,31,179,153,193,147,215,146,85,9,96,123,219,164,195,170,107,233,178,158,99,94,249,52,187,207],[180,201,6,172,2,243,102,48,245,131,13,38,125,29,147,84,41,138,173,82,226,184,74,214,165,231,122,38,9,51,73,181],[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0]],\"recentBlockhash\":[30,154,253,182,227,181,136,44,127,146,246,92,11,83,117,143,30,210,87,19,122,69,121,181,199,53,54,26,250,255,141,148],\"instructions\":[[2],{\"programIdIndex\":3,\"accounts\":[[2],0,1],\"data\":[[12],2,0,0,0,255,224,245,5,0,0,0,0]},{\"programIdIndex\":3,\"accounts\":[[2],0,2],\"data\":[[12],2,0,0,0,255,224,245,5,0,0,0,0]}]}}";
+
 #[rocket::async_test]
 async fn test_send_transaction() {
     dotenv::dotenv().ok();
@@ -259,7 +400,7 @@ async fn test_send_transaction() {
     let rocket = common::setup().await;
     let client = Client::tracked(rocket).await.unwrap();
 
-    let request : SendTransactionRequest = SendTransactionRequest { signed_transaction: "{\"signatures\":[[1],[192,121,121,143,216,202,102,196,11,13,214,44,86,244,245,201,28,233,91,194,78,106,95,42,171,37,195,24,63,33,93,82,230,248,1,203,171,207,190,0,71,82,19,206,53,211,198,27,89,6,220,46,116,17,142,154,62,226,182,118,134,10,211,10]],\"message\":{\"header\":{\"numRequiredSignatures\":1,\"numReadonlySignedAccounts\":0,\"numReadonlyUnsignedAccounts\":1},\"accountKeys\":[[4],[15,53,60,36,1,78,135,209,144,88,0,25,72,28,15,76,170,126,38,26,203,71,44,132,38,182,16,114,73,30,145,252],[149,95,26,86,184,220,32,31,179,153,193,147,215,146,85,9,96,123,219,164,195,170,107,233,178,158,99,94,249,52,187,207],[180,201,6,172,2,243,102,48,245,131,13,38,125,29,147,84,41,138,173,82,226,184,74,214,165,231,122,38,9,51,73,181],[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0]],\"recentBlockhash\":[30,154,253,182,227,181,136,44,127,146,246,92,11,83,117,143,30,210,87,19,122,69,121,181,199,53,54,26,250,255,141,148],\"instructions\":[[2],{\"programIdIndex\":3,\"accounts\":[[2],0,1],\"data\":[[12],2,0,0,0,255,224,245,5,0,0,0,0]},{\"programIdIndex\":3,\"accounts\":[[2],0,2],\"data\":[[12],2,0,0,0,255,224,245,5,0,0,0,0]}]}}".to_string()};
+    let request : SendTransactionRequest = SendTransactionRequest { signed_transaction: SIGNED_TRANSACTION.to_string()};
 
     let json_payload = rocket::serde::json::to_string(&request).unwrap();
 
@@ -277,4 +418,1395 @@ async fn test_send_transaction() {
       });
   
     assert_json_eq!(actual_response, expected_response);
+}
+#[test]
+fn test_config_validation() {
+    let default_config = Config::default();
+    assert!(default_config.validate().is_ok());
+    assert_eq!(default_config.rpc_url(), "https://api.devnet.solana.com");
+
+    let custom_without_url = Config {
+        cluster: Cluster::Custom,
+        rpc_url: None,
+        ..Config::default()
+    };
+    assert!(custom_without_url.validate().is_err());
+
+    let zero_timeout = Config {
+        timeout_secs: 0,
+        ..Config::default()
+    };
+    assert!(zero_timeout.validate().is_err());
+
+    let keystore_without_passphrase = Config {
+        signer: SignerKind::Keystore,
+        ..Config::default()
+    };
+    assert!(keystore_without_passphrase.validate().is_err());
+
+    let remote_without_url = Config {
+        signer: SignerKind::Remote,
+        ..Config::default()
+    };
+    assert!(remote_without_url.validate().is_err());
+
+    let percentile_above_100 = Config {
+        priority_fee_percentile: 101,
+        ..Config::default()
+    };
+    assert!(percentile_above_100.validate().is_err());
+
+    let removed_precision = Config {
+        precision: Some(9),
+        ..Config::default()
+    };
+    assert!(removed_precision.validate().is_err());
+}
+
+#[rocket::async_test]
+async fn test_rpc_pool_failover() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8010");
+
+    let rocket = common::setup_with(|config| {
+        let mock_url = config.rpc_url.take().unwrap();
+        // Nothing listens on the discard port, the pool has to fail over to the mock server
+        config.rpc_url = Some("http://127.0.0.1:9".to_string());
+        config.fallback_rpc_urls = vec![mock_url];
+    }).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let block_status = client.get("/blocks/latest").dispatch().await.status();
+
+    let status_response = client.get("/rpc/status").dispatch().await;
+    let actual_status = status_response.status();
+    let actual_response: Value = serde_json::from_str(status_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(block_status, Status::Ok);
+    assert_eq!(actual_status, Status::Ok);
+
+    let endpoints = actual_response["endpoints"].as_array().unwrap();
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(endpoints[0]["url"], "http://127.0.0.1:9");
+    assert_eq!(endpoints[0]["healthy"], false);
+    assert!(endpoints[0]["lastError"].is_string());
+    assert_eq!(endpoints[1]["healthy"], true);
+}
+
+#[rocket::async_test]
+async fn test_rpc_pool_health_check() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8011");
+
+    let rocket = common::setup_with(|config| {
+        config.fallback_rpc_urls = vec!["http://127.0.0.1:9".to_string()];
+    }).await;
+    rocket.state::<Arc<RpcPool>>().unwrap().check_health().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response = client.get("/rpc/status").dispatch().await;
+    let actual_status = response.status();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+
+    let endpoints = actual_response["endpoints"].as_array().unwrap();
+    assert_eq!(endpoints[0]["healthy"], true);
+    assert_eq!(endpoints[0]["slot"], 234381065);
+    assert_eq!(endpoints[0]["slotLag"], 0);
+    assert_eq!(endpoints[1]["healthy"], false);
+    assert_eq!(endpoints[1]["slot"], Value::Null);
+}
+
+#[rocket::async_test]
+async fn test_commitment_parameter() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8012");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let balance_response = client.get("/address/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN/balance?commitment=processed").dispatch().await;
+    let balance_status = balance_response.status();
+    let balance: Value = serde_json::from_str(balance_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    let block_response = client.get("/blocks/latest?commitment=processed").dispatch().await;
+    let block_status = block_response.status();
+    let block: Value = serde_json::from_str(block_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    let confirmations_response = client.get("/transactions/2Gqx5zXq8GUvbWZcKBjQzCiFTioYXLdWz7PQ86pvBJftnbtQLkfvVcfiRD271E3bbeP6FjQWc7DFALdZjtAEbW2y/confirmations?commitment=confirmed").dispatch().await;
+    let confirmations_status = confirmations_response.status();
+    let confirmations: Value = serde_json::from_str(confirmations_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(balance_status, Status::Ok);
+    assert_eq!(block_status, Status::Ok);
+    assert_eq!(confirmations_status, Status::Ok);
+
+    assert_eq!(balance["commitment"], "processed");
+    // Blocks are not available at processed commitment
+    assert_eq!(block["commitment"], "confirmed");
+    assert_eq!(confirmations["commitment"], "confirmed");
+}
+
+#[rocket::async_test]
+async fn test_get_latest_block_with_transactions() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8013");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response = client.get("/blocks/latest?includeTransactions=true").dispatch().await;
+
+    let actual_status = response.status();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+    assert_eq!(actual_response["slot"], 234381065);
+    assert_eq!(actual_response["blockHeight"], 428);
+
+    let transactions = actual_response["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0]["hash"], "2nBhEBYYvfaAe16UMNqRHre4YNSskvuYgx3M6E4JP1oDYvZEJHvoPzyUidNgNX5r9sTyN1J9UxtbCXy2rqYcuyuv");
+}
+
+#[rocket::async_test]
+async fn test_api_version_2() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8014");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let block_response = client.get("/blocks/234381065").header(Header::new("X-Api-Version", "2")).dispatch().await;
+    let block_status = block_response.status();
+    let block: Value = serde_json::from_str(block_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    let transaction_response = client.get("/transactions/2Gqx5zXq8GUvbWZcKBjQzCiFTioYXLdWz7PQ86pvBJftnbtQLkfvVcfiRD271E3bbeP6FjQWc7DFALdZjtAEbW2y/detail").header(Header::new("X-Api-Version", "2")).dispatch().await;
+    let transaction_status = transaction_response.status();
+    let transaction: Value = serde_json::from_str(transaction_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    let unsupported_status = client.get("/blocks/latest").header(Header::new("X-Api-Version", "3")).dispatch().await.status();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(block_status, Status::Ok);
+    assert_eq!(transaction_status, Status::Ok);
+    assert_eq!(unsupported_status, Status::BadRequest);
+
+    assert!(block.get("height").is_none());
+    assert_eq!(block["slot"], 234381065);
+    assert_eq!(block["blockHeight"], 428);
+    assert_eq!(block["transactions"][0]["slot"], 234381065);
+    assert_eq!(block["transactions"][0]["blockHeight"], 428);
+
+    assert_eq!(transaction["slot"], 430);
+    assert_eq!(transaction["blockHeight"], 428);
+}
+
+#[rocket::async_test]
+async fn test_scan_block_range() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8015");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    // The range ends after the latest slot 234381065 and gets clamped
+    let range_response = client.get("/blocks?from=234381063&to=234381070").dispatch().await;
+    let range_status = range_response.status();
+    let range: Value = serde_json::from_str(range_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    let limit_response = client.get("/blocks?from=234381063&limit=2").dispatch().await;
+    let limit_status = limit_response.status();
+    let limit: Value = serde_json::from_str(limit_response.into_string().await.unwrap().as_ref()).unwrap();
+
+    let invalid_status = client.get("/blocks?from=234381065&to=234381063").dispatch().await.status();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(range_status, Status::Ok);
+    assert_eq!(limit_status, Status::Ok);
+    assert_eq!(invalid_status, Status::BadRequest);
+
+    for response in [&range, &limit] {
+        assert_eq!(response["from"], 234381063);
+        assert_eq!(response["to"], 234381065);
+        assert_eq!(response["skippedSlots"], json!([234381064]));
+
+        let blocks = response["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0]["slot"], 234381063);
+        assert_eq!(blocks[1]["slot"], 234381065);
+        assert_eq!(blocks[1]["transactions"].as_array().unwrap().len(), 1);
+    }
+}
+
+#[rocket::async_test]
+async fn test_scan_block_address_filter() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8016");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    // The wallet 22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH only receives tokens on its associated token account
+    let mut transaction_counts = Vec::new();
+    let mut deposits = Vec::new();
+    for uri in [
+        "/blocks/234381070",
+        "/blocks/234381070?addresses=3UVYmECPPMZSCqWKfENfuoTv51fTDTWicX9xmBD2euKe,22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH",
+        "/blocks/234381070?addresses=Cwaw9f114aXGpm2RGiP7HrFYTL1ERLyZu21iDc1CSFZY",
+        "/blocks/234381070?contracts=usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+        "/blocks/234381070?addresses=3UVYmECPPMZSCqWKfENfuoTv51fTDTWicX9xmBD2euKe",
+        "/blocks/234381070?contracts=3wyAj7Rt1TWVPZVteFJPLa26JmLvdb1CAKEFZm3NY75E",
+    ] {
+        let response = client.get(uri).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let block: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+        let transactions = block["transactions"].as_array().unwrap();
+        transaction_counts.push(transactions.len());
+        deposits.extend(transactions.iter().map(|transaction| transaction["to"].clone()));
+    }
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(transaction_counts, vec![1, 1, 1, 1, 0, 0]);
+    assert_json_eq!(deposits[1], json!([{
+        "adress": "Cwaw9f114aXGpm2RGiP7HrFYTL1ERLyZu21iDc1CSFZY",
+        "amount": {"amount": "5000000", "decimals": 6, "uiAmountString": "5"},
+        "contract": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+        "owner": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH"
+    }]));
+}
+
+#[rocket::async_test]
+async fn test_get_v0_transaction_details() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8017");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response = client.get("/transactions/4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9/detail").dispatch().await;
+
+    let actual_status = response.status();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+
+    // The recipient and the token account are only known through the address lookup table
+    let expected_response = json!({
+      "blockHash":"5HGWYu788RuwwKLBkwXsiRdxMHbRSWqXvvPCdkxC6Cn3",
+      "blockHeight":234381070,
+      "slot":234381070,
+      "blockTime":1690000000,
+      "fee":{"amount":"5000","decimals":9,"uiAmountString":"0.000005"},
+      "from":[{"adress":"B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN","amount":{"amount":"1000005000","decimals":9,"uiAmountString":"1.000005"},"contract":null}],
+      "to":[
+        {"adress":"22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH","amount":{"amount":"1000000000","decimals":9,"uiAmountString":"1"},"contract":null},
+        {"adress":"Cwaw9f114aXGpm2RGiP7HrFYTL1ERLyZu21iDc1CSFZY","amount":{"amount":"5000000","decimals":6,"uiAmountString":"5"},"contract":"usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs","owner":"22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH"}
+      ],
+      "hash":"4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9",
+      "status":"Success",
+      "commitment":"finalized"
+    });
+
+    assert_json_eq!(actual_response, expected_response);
+}
+
+#[rocket::async_test]
+async fn test_get_binary_and_parsed_transaction_details() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8018");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let mut transactions = Vec::new();
+    for txn_hash in [
+        "4fXvvk1kZiuBjz4J7AcVJF4QaL2pPRDBHeqKrWvMsq3L6hUT7xZXPefDdNuARk2bkpaQGRn2LSJjMixm62ecmf8b",
+        "2Ana1pUpv2ZbMVkwF5FXapYeBEjdxDatLn7nvJkhgTSXbs59SyZSx866bXirPgj8QQVB57uxHJBG1YFvkRbFj4T",
+    ] {
+        let response = client.get(format!("/transactions/{}/detail", txn_hash)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let transaction: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+        transactions.push((txn_hash, transaction));
+    }
+
+    client.terminate().await.shutdown().await;
+
+    // Base64 and jsonParsed encodings of the same transfer are reported identically
+    for (txn_hash, transaction) in transactions {
+        assert_eq!(transaction["hash"], txn_hash);
+        assert_eq!(transaction["status"], "Success");
+        assert_json_eq!(transaction["from"], json!([{"adress":"B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN","amount":{"amount":"1000005000","decimals":9,"uiAmountString":"1.000005"},"contract":null}]));
+        assert_json_eq!(transaction["to"], json!([{"adress":"22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH","amount":{"amount":"1000000000","decimals":9,"uiAmountString":"1"},"contract":null}]));
+    }
+}
+
+#[rocket::async_test]
+async fn test_sign_transaction_exact_amounts() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8019");
+
+    let rocket = common::setup_with_test_key().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let mut statuses = Vec::new();
+    for (to_amount, contract) in [
+        // Fractional SOL given as a decimal string is converted to lamports without rounding
+        (json!({"uiAmountString": "0.000000001", "decimals": 9}), Value::Null),
+        (json!({"amount": "1500000", "decimals": 6}), json!("usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs")),
+        // SOL has 9 decimals, the mint 6
+        (json!({"amount": "1500000", "decimals": 6}), Value::Null),
+        (json!({"amount": "1500000", "decimals": 9}), json!("usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs")),
+        (json!({"amount": "1", "decimals": 9, "uiAmountString": "1"}), Value::Null),
+    ] {
+        let request = json!({
+            "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": to_amount, "contract": contract}],
+            "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": to_amount, "contract": contract}],
+            "keyId": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN"
+        });
+        let response = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+        statuses.push(response.status());
+    }
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(statuses, vec![Status::Ok, Status::Ok, Status::BadRequest, Status::BadRequest, Status::UnprocessableEntity]);
+}
+
+#[rocket::async_test]
+async fn test_sign_token_transaction() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8020");
+
+    let rocket = common::setup_with_test_key().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let request = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "1500000", "decimals": 6}, "contract": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs"}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1500000", "decimals": 6}, "contract": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs"}],
+        "keyId": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+        "createAssociatedTokenAccount": true
+    });
+    let response = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+
+    let actual_status = response.status();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+
+    let transaction: Transaction = serde_json::from_str(actual_response["signedTransaction"].as_str().unwrap()).unwrap();
+    let message = &transaction.message;
+    let sender = Pubkey::from_str("B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN").unwrap();
+    let recipient = Pubkey::from_str("22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH").unwrap();
+    let mint = Pubkey::from_str("usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs").unwrap();
+
+    // The recipient has no token account yet, it is created before the transfer
+    assert_eq!(message.instructions.len(), 2);
+    assert_eq!(message.account_keys[message.instructions[0].program_id_index as usize], spl_associated_token_account::id());
+    assert_eq!(message.account_keys[message.instructions[1].program_id_index as usize], spl_token::id());
+
+    let transfer = &message.instructions[1];
+    let transfer_accounts: Vec<Pubkey> = transfer.accounts.iter().map(|index| message.account_keys[*index as usize]).collect();
+    assert_eq!(transfer_accounts, vec![get_associated_token_address(&sender, &mint), mint, get_associated_token_address(&recipient, &mint), sender]);
+    // TransferChecked with 1500000 base units and 6 decimals
+    assert_eq!(transfer.data, [vec![12], 1500000_u64.to_le_bytes().to_vec(), vec![6]].concat());
+}
+
+#[rocket::async_test]
+async fn test_sign_token_2022_transaction() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8021");
+
+    let rocket = common::setup_with_test_key().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let request = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "100000", "decimals": 6}, "contract": "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo"}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "100000", "decimals": 6}, "contract": "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo"}],
+        "keyId": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+        "createAssociatedTokenAccount": true
+    });
+    let response = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+
+    let actual_status = response.status();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+
+    let transaction: Transaction = serde_json::from_str(actual_response["signedTransaction"].as_str().unwrap()).unwrap();
+    let message = &transaction.message;
+    let sender = Pubkey::from_str("B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN").unwrap();
+    let recipient = Pubkey::from_str("22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH").unwrap();
+    let mint = Pubkey::from_str("2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo").unwrap();
+
+    assert_eq!(message.instructions.len(), 2);
+    assert_eq!(message.account_keys[message.instructions[0].program_id_index as usize], spl_associated_token_account::id());
+    assert_eq!(message.account_keys[message.instructions[1].program_id_index as usize], spl_token_2022::id());
+
+    let transfer = &message.instructions[1];
+    let transfer_accounts: Vec<Pubkey> = transfer.accounts.iter().map(|index| message.account_keys[*index as usize]).collect();
+    assert_eq!(transfer_accounts, vec![
+        get_associated_token_address_with_program_id(&sender, &mint, &spl_token_2022::id()),
+        mint,
+        get_associated_token_address_with_program_id(&recipient, &mint, &spl_token_2022::id()),
+        sender
+    ]);
+    // TransferCheckedWithFee of 100000 base units with 6 decimals, 0.5% of fee
+    assert_eq!(transfer.data, [vec![26, 1], 100000_u64.to_le_bytes().to_vec(), vec![6], 500_u64.to_le_bytes().to_vec()].concat());
+}
+
+#[rocket::async_test]
+async fn test_address_balance_token_2022() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8022");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response = client.get("/address/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN/balance?contract=2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo").dispatch().await;
+
+    let actual_status = response.status();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+
+    // Larger than what a f64 holds exactly, summed over all the token accounts of the mint
+    let expected_response = json!({
+        "balance": {"amount": "123456789013345678", "decimals": 6, "uiAmountString": "123456789013.345678"},
+        "accounts": [
+            {
+                "address": "7UX2i7SucgLMQcfZ75s3VXmZZY4YRUyJN9X1RgfMoDUi",
+                "amount": {"amount": "123456789012345678", "decimals": 6, "uiAmountString": "123456789012.345678"},
+                "frozen": false,
+                "delegate": null
+            },
+            {
+                "address": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+                "amount": {"amount": "1000000", "decimals": 6, "uiAmountString": "1"},
+                "frozen": true,
+                "delegate": "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T"
+            }
+        ],
+        "commitment": "finalized"
+    });
+
+    assert_json_eq!(actual_response, expected_response);
+}
+
+#[rocket::async_test]
+async fn test_address_tokens() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8023");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response = client.get("/address/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN/tokens").dispatch().await;
+
+    let actual_status = response.status();
+    let actual_response: Value = serde_json::from_str(response.into_string().await.unwrap().as_ref()).unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+
+    let expected_response = json!({
+        "address": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+        "balance": {"amount": "2000000000", "decimals": 9, "uiAmountString": "2"},
+        "tokens": [
+            {
+                "contract": "usdxvpXrXHw8WEDrNbj3EPdJaUopvrNDXToCPHSnaEs",
+                "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "balance": {"amount": "5000000", "decimals": 6, "uiAmountString": "5"},
+                "accounts": [
+                    {
+                        "address": "C2gJg6tKpQs41PRS1nC8aw3ZKNZK3HQQZGVrDFDup5nx",
+                        "amount": {"amount": "5000000", "decimals": 6, "uiAmountString": "5"},
+                        "frozen": false,
+                        "delegate": null
+                    }
+                ]
+            },
+            {
+                "contract": "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo",
+                "programId": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+                "balance": {"amount": "123456789013345678", "decimals": 6, "uiAmountString": "123456789013.345678"},
+                "accounts": [
+                    {
+                        "address": "7UX2i7SucgLMQcfZ75s3VXmZZY4YRUyJN9X1RgfMoDUi",
+                        "amount": {"amount": "123456789012345678", "decimals": 6, "uiAmountString": "123456789012.345678"},
+                        "frozen": false,
+                        "delegate": null
+                    },
+                    {
+                        "address": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+                        "amount": {"amount": "1000000", "decimals": 6, "uiAmountString": "1"},
+                        "frozen": true,
+                        "delegate": "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T"
+                    }
+                ]
+            }
+        ],
+        "commitment": "finalized"
+    });
+
+    assert_json_eq!(actual_response, expected_response);
+}
+
+#[rocket::async_test]
+async fn test_address_transactions() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8024");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response_hydrated = client.get("/address/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN/transactions?limit=2&includeTransactions=true").dispatch().await;
+    let actual_status_hydrated = response_hydrated.status();
+    let actual_response_hydrated: Value = serde_json::from_str(response_hydrated.into_string().await.unwrap().as_ref()).unwrap();
+
+    let response_last_page = client.get("/address/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN/transactions?before=3ZqNkNT4a9r3E1mGGhXESiAqEhpSF5TpkUEBYKH7cfNXhNBVe1EEbm67RjvVfHbvkoRVPHMu4wV6PdrukjjyYvHn&limit=10").dispatch().await;
+    let actual_status_last_page = response_last_page.status();
+    let actual_response_last_page: Value = serde_json::from_str(response_last_page.into_string().await.unwrap().as_ref()).unwrap();
+
+    let invalid_limit_status = client.get("/address/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN/transactions?limit=0").dispatch().await.status();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status_hydrated, Status::Ok);
+    assert_eq!(actual_status_last_page, Status::Ok);
+    assert_eq!(invalid_limit_status, Status::BadRequest);
+
+    // A full page points to the next one
+    assert_eq!(actual_response_hydrated["nextBefore"], "2Ana1pUpv2ZbMVkwF5FXapYeBEjdxDatLn7nvJkhgTSXbs59SyZSx866bXirPgj8QQVB57uxHJBG1YFvkRbFj4T");
+    let transactions = actual_response_hydrated["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0]["status"], "Success");
+    assert_eq!(transactions[1]["status"], "Failed");
+    assert_eq!(transactions[0]["transaction"]["hash"], transactions[0]["hash"]);
+    assert_eq!(transactions[0]["transaction"]["blockHash"], "5HGWYu788RuwwKLBkwXsiRdxMHbRSWqXvvPCdkxC6Cn3");
+
+    let expected_response_last_page = json!({
+        "address": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN",
+        "transactions": [
+            {
+                "hash": "4fXvvk1kZiuBjz4J7AcVJF4QaL2pPRDBHeqKrWvMsq3L6hUT7xZXPefDdNuARk2bkpaQGRn2LSJjMixm62ecmf8b",
+                "slot": 234381070,
+                "blockTime": 1690000000,
+                "status": "Success",
+                "confirmationStatus": "finalized"
+            },
+            {
+                "hash": "2Ana1pUpv2ZbMVkwF5FXapYeBEjdxDatLn7nvJkhgTSXbs59SyZSx866bXirPgj8QQVB57uxHJBG1YFvkRbFj4T",
+                "slot": 234381070,
+                "blockTime": 1690000000,
+                "status": "Failed",
+                "confirmationStatus": "finalized"
+            }
+        ],
+        "nextBefore": null,
+        "commitment": "finalized"
+    });
+
+    assert_json_eq!(actual_response_last_page, expected_response_last_page);
+}
+
+
+#[rocket::async_test]
+async fn test_sign_transaction_key_id() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8025");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let wallet: Value = client.post("/address").dispatch().await.into_json().await.unwrap();
+    let address = wallet["address"].as_str().unwrap();
+    let transfer = |from: &str, key: Value| json!({
+        "from": [{"adress": from, "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+        "keyId": key["keyId"],
+        "private_key": key["private_key"]
+    }).to_string();
+
+    let response = client.post("/transactions/sign").header(ContentType::JSON).body(transfer(address, json!({"keyId": wallet["keyId"]}))).dispatch().await;
+    let signed_status = response.status();
+    let signed: Value = response.into_json().await.unwrap();
+
+    let mut statuses = Vec::new();
+    for (from, key) in [
+        // Private keys are rejected outside of the insecure mode
+        (address, json!({"private_key": "3J5zuqwqdecmVY7Xvk5T9j4ks1LTYAiq7mxsenuXGaZH"})),
+        // Without key ID the key of the sender address is used
+        (address, json!({})),
+        ("B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", json!({"keyId": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN"})),
+        // The key has to be the one of the sender
+        ("B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", json!({"keyId": wallet["keyId"]})),
+    ] {
+        let response = client.post("/transactions/sign").header(ContentType::JSON).body(transfer(from, key)).dispatch().await;
+        statuses.push(response.status());
+    }
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(signed_status, Status::Ok);
+    let transaction: Transaction = serde_json::from_str(signed["signedTransaction"].as_str().unwrap()).unwrap();
+    assert_eq!(transaction.message.account_keys[0].to_string(), address);
+    assert!(transaction.verify().is_ok());
+
+    assert_eq!(statuses, vec![Status::Forbidden, Status::Ok, Status::NotFound, Status::BadRequest]);
+}
+
+#[rocket::async_test]
+async fn test_keystore_signer() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8026");
+
+    let keystore_path = env::temp_dir().join(format!("blockchain-solana-keystore-{}", std::process::id()));
+    let path = keystore_path.clone();
+    let rocket = common::setup_with(|config| {
+        config.signer = SignerKind::Keystore;
+        config.keystore_path = path.to_string_lossy().to_string();
+        config.keystore_passphrase = Some("correct horse battery staple".to_string());
+        config.keystore_scrypt_log_n = 10;
+    }).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let wallet: Value = client.post("/address").dispatch().await.into_json().await.unwrap();
+    let address = wallet["address"].as_str().unwrap().to_string();
+    let request = json!({
+        "from": [{"adress": address, "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+        "keyId": wallet["keyId"]
+    });
+    let response = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+    let actual_status = response.status();
+    let actual_response: Value = response.into_json().await.unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(actual_status, Status::Ok);
+    let transaction: Transaction = serde_json::from_str(actual_response["signedTransaction"].as_str().unwrap()).unwrap();
+    assert!(transaction.verify().is_ok());
+
+    let key_file: Value = serde_json::from_slice(&std::fs::read(keystore_path.join(format!("{}.json", address))).unwrap()).unwrap();
+    assert_eq!(key_file["address"], address);
+    assert_eq!(key_file["crypto"]["kdf"], "scrypt");
+    assert_eq!(key_file["crypto"]["cipher"], "aes-256-gcm");
+
+    let wrong_passphrase = KeystoreSigner::new(&keystore_path, "wrong passphrase".to_string(), 10);
+    let result = wrong_passphrase.sign_message(&address, b"message").await;
+    std::fs::remove_dir_all(&keystore_path).unwrap();
+
+    assert!(matches!(result, Err(ResponseError::SignerError(_))));
+}
+
+#[rocket::async_test]
+async fn test_remote_signer() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8027");
+
+    // Stand-in of the signing service, holding the key of B45r...
+    let remote_signer = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/keys/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"keyId": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "publicKey": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN"})))
+        .mount(&remote_signer)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/keys/B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN/sign"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let message = BASE64.decode(body["message"].as_str().unwrap()).unwrap();
+            ResponseTemplate::new(200).set_body_json(json!({"signature": common::test_keypair().sign_message(&message).to_string()}))
+        })
+        .mount(&remote_signer)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/keys"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"keyId": "custody-1", "publicKey": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH"})))
+        .mount(&remote_signer)
+        .await;
+
+    let remote_signer_url = remote_signer.uri();
+    let rocket = common::setup_with(|config| {
+        config.signer = SignerKind::Remote;
+        config.remote_signer_url = Some(remote_signer_url);
+    }).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let wallet: Value = client.post("/address").dispatch().await.into_json().await.unwrap();
+
+    let mut responses = Vec::new();
+    for key_id in ["B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "unknown"] {
+        let request = json!({
+            "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+            "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+            "keyId": key_id
+        });
+        let response = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+        responses.push((response.status(), response.into_json::<Value>().await.unwrap()));
+    }
+
+    client.terminate().await.shutdown().await;
+
+    assert_json_eq!(wallet, json!({"address": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "keyId": "custody-1"}));
+
+    assert_eq!(responses[0].0, Status::Ok);
+    let transaction: Transaction = serde_json::from_str(responses[0].1["signedTransaction"].as_str().unwrap()).unwrap();
+    assert!(transaction.verify().is_ok());
+    assert_eq!(responses[1].0, Status::NotFound);
+}
+
+#[rocket::async_test]
+async fn test_keystore_key_management() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8028");
+
+    let keystore_path = env::temp_dir().join(format!("blockchain-solana-keys-{}", std::process::id()));
+    let path = keystore_path.clone();
+    let rocket = common::setup_with(|config| {
+        config.signer = SignerKind::Keystore;
+        config.keystore_path = path.to_string_lossy().to_string();
+        config.keystore_passphrase = Some("correct horse battery staple".to_string());
+        config.keystore_scrypt_log_n = 10;
+    }).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let deposit: Value = client.post("/address").header(ContentType::JSON).body(json!({"label": "deposit"}).to_string()).dispatch().await.into_json().await.unwrap();
+    let hot: Value = client.post("/address").dispatch().await.into_json().await.unwrap();
+    let deposit_address = deposit["address"].as_str().unwrap().to_string();
+    let hot_address = hot["address"].as_str().unwrap().to_string();
+
+    let listed: Value = client.get("/keys").dispatch().await.into_json().await.unwrap();
+
+    let archive_response = client.patch(format!("/keys/{}", deposit_address)).header(ContentType::JSON).body(json!({"archived": true, "label": "old deposit"}).to_string()).dispatch().await;
+    let archive_status = archive_response.status();
+    let archived: Value = archive_response.into_json().await.unwrap();
+    let listed_active: Value = client.get("/keys").dispatch().await.into_json().await.unwrap();
+    let listed_all: Value = client.get("/keys?archived=true").dispatch().await.into_json().await.unwrap();
+
+    // Signing by address, archived keys cannot sign anymore
+    let mut statuses = Vec::new();
+    for address in [&hot_address, &deposit_address] {
+        let request = json!({
+            "from": [{"adress": address, "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+            "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}]
+        });
+        let response = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+        statuses.push(response.status());
+    }
+    let unknown_status = client.patch("/keys/22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH").header(ContentType::JSON).body(json!({"archived": true}).to_string()).dispatch().await.status();
+
+    client.terminate().await.shutdown().await;
+
+    let key_file: Value = serde_json::from_slice(&std::fs::read(keystore_path.join(format!("{}.json", deposit_address))).unwrap()).unwrap();
+    std::fs::remove_dir_all(&keystore_path).unwrap();
+
+    assert_eq!(deposit["label"], "deposit");
+    assert_eq!(deposit["keyId"], deposit_address);
+    assert!(hot.get("label").is_none());
+
+    let listed_addresses = |list: &Value| -> Vec<String> {
+        let mut addresses: Vec<String> = list["keys"].as_array().unwrap().iter().map(|key| key["address"].as_str().unwrap().to_string()).collect();
+        addresses.sort();
+        addresses
+    };
+    let mut both = vec![deposit_address.clone(), hot_address.clone()];
+    both.sort();
+    assert_eq!(listed_addresses(&listed), both);
+    assert_eq!(listed_addresses(&listed_active), vec![hot_address.clone()]);
+    assert_eq!(listed_addresses(&listed_all), both);
+
+    assert_eq!(archive_status, Status::Ok);
+    assert_eq!(archived["label"], "old deposit");
+    assert_eq!(archived["archived"], true);
+    assert_eq!(key_file["label"], "old deposit");
+    assert_eq!(key_file["archived"], true);
+
+    assert_eq!(statuses, vec![Status::Ok, Status::BadRequest]);
+    assert_eq!(unknown_status, Status::NotFound);
+}
+
+
+#[rocket::async_test]
+async fn test_hd_wallet() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8029");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    let wallet: Value = client.post("/hd-wallets").header(ContentType::JSON).body(json!({"mnemonic": mnemonic, "label": "deposits"}).to_string()).dispatch().await.into_json().await.unwrap();
+    let wallet_id = wallet["walletId"].as_str().unwrap().to_string();
+
+    let mut derived = Vec::new();
+    for request in [json!({}), json!({"label": "customer 1"}), json!({"index": 0})] {
+        let response = client.post(format!("/hd-wallets/{}/addresses", wallet_id)).header(ContentType::JSON).body(request.to_string()).dispatch().await;
+        derived.push(response.into_json::<Value>().await.unwrap());
+    }
+    let imported_again: Value = client.post("/hd-wallets").header(ContentType::JSON).body(json!({"mnemonic": mnemonic}).to_string()).dispatch().await.into_json().await.unwrap();
+    // Nobody would ever see the seed, it is lost on restart
+    let generated_status = client.post("/hd-wallets").dispatch().await.status();
+
+    let request = json!({
+        "from": [{"adress": derived[1]["address"], "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}]
+    });
+    let sign_status = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await.status();
+
+    let mut statuses = Vec::new();
+    statuses.push(client.post("/hd-wallets").header(ContentType::JSON).body(json!({"mnemonic": "abandon abandon abandon"}).to_string()).dispatch().await.status());
+    statuses.push(client.post(format!("/hd-wallets/{}/addresses", wallet_id)).header(ContentType::JSON).body(json!({"index": 2147483648_u32}).to_string()).dispatch().await.status());
+    statuses.push(client.post("/hd-wallets/22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH/addresses").dispatch().await.status());
+
+    client.terminate().await.shutdown().await;
+
+    // Same addresses as the Solana CLI and Phantom for the mnemonic
+    assert_json_eq!(wallet, json!({"walletId": "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk", "label": "deposits", "nextIndex": 0}));
+    assert_eq!(derived[0]["address"], "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+    assert_eq!(derived[0]["derivationIndex"], 0);
+    assert_eq!(derived[0]["derivationPath"], "m/44'/501'/0'/0'");
+    assert_eq!(derived[0]["hdWallet"], wallet_id);
+    assert_eq!(derived[1]["derivationIndex"], 1);
+    assert_eq!(derived[1]["derivationPath"], "m/44'/501'/1'/0'");
+    assert_eq!(derived[1]["label"], "customer 1");
+    assert_eq!(derived[2], derived[0]);
+    assert_eq!(imported_again["walletId"], wallet_id);
+    assert_eq!(imported_again["nextIndex"], 2);
+    assert_eq!(generated_status, Status::BadRequest);
+
+    assert_eq!(sign_status, Status::Ok);
+    assert_eq!(statuses, vec![Status::BadRequest, Status::BadRequest, Status::NotFound]);
+
+    // The keystore keeps the seed and the next index across restarts
+    let keystore_path = env::temp_dir().join(format!("blockchain-solana-hd-{}", std::process::id()));
+    let seed = generate_seed_from_seed_phrase_and_passphrase(mnemonic, "");
+    let keystore = KeystoreSigner::new(&keystore_path, "correct horse battery staple".to_string(), 10);
+    let keystore_wallet = keystore.import_seed(&seed, None).await.unwrap();
+    let first = keystore.derive_key(&keystore_wallet.wallet_id, None, None).await.unwrap();
+    let restarted = KeystoreSigner::new(&keystore_path, "correct horse battery staple".to_string(), 10);
+    let second = restarted.derive_key(&keystore_wallet.wallet_id, None, None).await.unwrap();
+    let keys = restarted.list_keys(false).await.unwrap();
+    std::fs::remove_dir_all(&keystore_path).unwrap();
+
+    assert_eq!(keystore_wallet.wallet_id, wallet_id);
+    assert_eq!(first.address, "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+    assert_eq!(second.address, derived[1]["address"].as_str().unwrap());
+    assert_eq!(second.derivation_index, Some(1));
+    assert_eq!(keys.len(), 2);
+}
+
+
+#[rocket::async_test]
+async fn test_build_and_combine_transaction() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8030");
+
+    let rocket = common::setup_with_test_key().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let transfer = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "200000000", "decimals": 9}, "contract": null}],
+        "to": [
+            {"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "99999999", "decimals": 9}, "contract": null},
+            {"adress": "DAiB6ZGYa5xXuTMjsNVrS4PEnzutWB33HeQM6gmPJSZi", "amount": {"amount": "99999999", "decimals": 9}, "contract": null}
+        ]
+    });
+    let response = client.post("/transactions/build").header(ContentType::JSON).body(transfer.to_string()).dispatch().await;
+    let build_status = response.status();
+    let built: Value = response.into_json().await.unwrap();
+
+    // Signed offline with the key of the sender
+    let message = BASE64.decode(built["message"].as_str().unwrap()).unwrap();
+    let keypair = common::test_keypair();
+    let signature = keypair.sign_message(&message).to_string();
+    let combine = |signatures: Value| json!({"message": built["message"], "signatures": signatures}).to_string();
+
+    let response = client.post("/transactions/combine").header(ContentType::JSON).body(combine(json!([{"publicKey": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "signature": signature}]))).dispatch().await;
+    let combine_status = response.status();
+    let combined: Value = response.into_json().await.unwrap();
+    let signed: Value = client.post("/transactions/sign").header(ContentType::JSON).body(transfer.to_string()).dispatch().await.into_json().await.unwrap();
+
+    let mut statuses = Vec::new();
+    for request in [
+        combine(json!([])),
+        combine(json!([{"publicKey": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "signature": keypair.sign_message(b"another message").to_string()}])),
+        combine(json!([{"publicKey": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "signature": signature}])),
+        json!({"message": "not base64!", "signatures": []}).to_string(),
+    ] {
+        statuses.push(client.post("/transactions/combine").header(ContentType::JSON).body(request).dispatch().await.status());
+    }
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(build_status, Status::Ok);
+    assert_eq!(built["requiredSigners"], json!(["B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN"]));
+    assert_eq!(built["recentBlockhash"], "5HGWYu788RuwwKLBkwXsiRdxMHbRSWqXvvPCdkxC6Cn3");
+    assert_eq!(built["lastValidBlockHeight"], 3090);
+
+    // The same transaction as signed by the service
+    assert_eq!(combine_status, Status::Ok);
+    assert_json_eq!(combined, signed);
+    assert_eq!(combined["txnHash"], signature);
+
+    assert_eq!(statuses, vec![Status::BadRequest, Status::BadRequest, Status::BadRequest, Status::BadRequest]);
+}
+
+
+#[rocket::async_test]
+async fn test_sign_transaction_multiple_signers() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8031");
+
+    let rocket = common::setup_with_test_key().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let treasury = "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN";
+    let wallet: Value = client.post("/address").dispatch().await.into_json().await.unwrap();
+    let user = wallet["address"].as_str().unwrap();
+    let external = Keypair::new();
+    let external_address = external.pubkey().to_string();
+    let account = |address: &str| json!({"adress": address, "amount": {"amount": "1000", "decimals": 9}, "contract": null});
+    let recipients = json!([account("22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH"), account("DAiB6ZGYa5xXuTMjsNVrS4PEnzutWB33HeQM6gmPJSZi")]);
+    let sign = |request: Value| client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch();
+
+    // The treasury sponsors the transfer of the user
+    let response = sign(json!({"from": [account(user)], "to": recipients, "feePayer": treasury})).await;
+    let sponsored_status = response.status();
+    let sponsored: Value = response.into_json().await.unwrap();
+
+    // Each sender pays the recipient at the same position
+    let response = sign(json!({"from": [account(user), account(treasury)], "to": recipients})).await;
+    let multiple_senders_status = response.status();
+    let multiple_senders: Value = response.into_json().await.unwrap();
+
+    // The key of the external sender is not in the backend
+    let partial_request = json!({"from": [account(&external_address)], "to": recipients, "feePayer": treasury, "partial": true});
+    let response = sign(partial_request.clone()).await;
+    let partial_status = response.status();
+    let partial: Value = response.into_json().await.unwrap();
+
+    let partial_transaction: Transaction = serde_json::from_str(partial["signedTransaction"].as_str().unwrap()).unwrap();
+    let signature = external.sign_message(&partial_transaction.message_data()).to_string();
+    let combine = json!({"signedTransaction": partial["signedTransaction"], "signatures": [{"publicKey": external_address, "signature": signature}]});
+    let response = client.post("/transactions/combine").header(ContentType::JSON).body(combine.to_string()).dispatch().await;
+    let combine_status = response.status();
+    let combined: Value = response.into_json().await.unwrap();
+
+    let mut statuses = Vec::new();
+    for request in [
+        json!({"from": [account(&external_address)], "to": recipients, "feePayer": treasury}),
+        json!({"from": [account(user), account(treasury)], "to": [account("22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH")]}),
+        json!({"from": [account(user)], "to": recipients, "feePayer": treasury, "keyIds": {treasury: wallet["keyId"]}}),
+    ] {
+        statuses.push(sign(request).await.status());
+    }
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(sponsored_status, Status::Ok);
+    let transaction: Transaction = serde_json::from_str(sponsored["signedTransaction"].as_str().unwrap()).unwrap();
+    assert_eq!(transaction.message.signer_keys().iter().map(|signer| signer.to_string()).collect::<Vec<_>>(), vec![treasury, user]);
+    assert!(transaction.verify().is_ok());
+    assert_eq!(sponsored["txnHash"], transaction.signatures[0].to_string());
+    assert!(sponsored.get("missingSignatures").is_none());
+
+    assert_eq!(multiple_senders_status, Status::Ok);
+    let transaction: Transaction = serde_json::from_str(multiple_senders["signedTransaction"].as_str().unwrap()).unwrap();
+    assert_eq!(transaction.message.signer_keys().iter().map(|signer| signer.to_string()).collect::<Vec<_>>(), vec![user, treasury]);
+    assert!(transaction.verify().is_ok());
+
+    assert_eq!(partial_status, Status::Ok);
+    assert_eq!(partial["missingSignatures"], json!([external_address]));
+    assert_eq!(partial["txnHash"], partial_transaction.signatures[0].to_string());
+    assert!(partial_transaction.verify().is_err());
+
+    assert_eq!(combine_status, Status::Ok);
+    let transaction: Transaction = serde_json::from_str(combined["signedTransaction"].as_str().unwrap()).unwrap();
+    assert!(transaction.verify().is_ok());
+    assert_eq!(combined["txnHash"], partial["txnHash"]);
+    assert!(combined.get("missingSignatures").is_none());
+
+    assert_eq!(statuses, vec![Status::NotFound, Status::BadRequest, Status::BadRequest]);
+}
+
+#[rocket::async_test]
+async fn test_sign_transaction_priority_fee() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8032");
+
+    let rocket = common::setup_with_test_key().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let transfer = |compute_budget: Value| {
+        let mut request = json!({
+            "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+            "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}]
+        });
+        request.as_object_mut().unwrap().extend(compute_budget.as_object().unwrap().clone());
+        request.to_string()
+    };
+
+    let mut transactions = Vec::new();
+    for compute_budget in [
+        json!({"computeUnitLimit": 1000, "priorityFeeMicroLamports": 25}),
+        // 75th percentile of the recent fees
+        json!({"priorityFeeMicroLamports": "auto"}),
+        json!({}),
+    ] {
+        let response = client.post("/transactions/sign").header(ContentType::JSON).body(transfer(compute_budget)).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let signed: Value = response.into_json().await.unwrap();
+        transactions.push(serde_json::from_str::<Transaction>(signed["signedTransaction"].as_str().unwrap()).unwrap());
+    }
+
+    let mut statuses = Vec::new();
+    for compute_budget in [
+        json!({"computeUnitLimit": 0}),
+        json!({"computeUnitLimit": 1400001}),
+        json!({"priorityFeeMicroLamports": "high"}),
+    ] {
+        statuses.push(client.post("/transactions/sign").header(ContentType::JSON).body(transfer(compute_budget)).dispatch().await.status());
+    }
+
+    client.terminate().await.shutdown().await;
+
+    let compute_budget_data = |transaction: &Transaction| transaction.message.instructions.iter()
+        .filter(|instruction| *instruction.program_id(&transaction.message.account_keys) == compute_budget::id())
+        .map(|instruction| instruction.data.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(compute_budget_data(&transactions[0]), vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(1000).data,
+        ComputeBudgetInstruction::set_compute_unit_price(25).data
+    ]);
+    assert_eq!(compute_budget_data(&transactions[1]), vec![ComputeBudgetInstruction::set_compute_unit_price(5000).data]);
+    assert!(compute_budget_data(&transactions[2]).is_empty());
+    // The compute budget instructions come before the transfer
+    assert_eq!(*transactions[0].message.instructions[2].program_id(&transactions[0].message.account_keys), solana_sdk::system_program::id());
+    assert!(transactions.iter().all(|transaction| transaction.verify().is_ok()));
+
+    assert_eq!(statuses, vec![Status::BadRequest, Status::BadRequest, Status::UnprocessableEntity]);
+}
+
+fn rpc_mock(rpc_method: &str, result: Value) -> Mock {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({"method": rpc_method})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"jsonrpc": "2.0", "result": result, "id": 1})))
+}
+
+fn ui_account(lamports: u64, owner: &str, executable: bool) -> Value {
+    json!({"data": ["", "base64"], "executable": executable, "lamports": lamports, "owner": owner, "rentEpoch": 0, "space": 0})
+}
+
+#[rocket::async_test]
+async fn test_simulate_transaction() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8034");
+
+    // The sender pays 1000 lamports and the fee of 5000 lamports, the recipient account is created by the transfer
+    let system_program = ui_account(1, "NativeLoader1111111111111111111111111111111", true);
+    let rocket = common::setup_with_rpc_mocks(vec![
+        rpc_mock("getMultipleAccounts", json!({
+            "context": {"slot": 234381065},
+            "value": [ui_account(1000000000, "11111111111111111111111111111111", false), null, system_program]
+        })),
+        rpc_mock("simulateTransaction", json!({
+            "context": {"slot": 234381065},
+            "value": {
+                "err": null,
+                "accounts": [ui_account(999994000, "11111111111111111111111111111111", false), ui_account(1000, "11111111111111111111111111111111", false), system_program],
+                "logs": ["Program 11111111111111111111111111111111 invoke [1]", "Program 11111111111111111111111111111111 success"],
+                "returnData": null,
+                "unitsConsumed": 150
+            }
+        })),
+    ]).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let transfer = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}]
+    });
+    let signed: Value = client.post("/transactions/sign").header(ContentType::JSON).body(transfer.to_string()).dispatch().await.into_json().await.unwrap();
+    let built: Value = client.post("/transactions/build").header(ContentType::JSON).body(transfer.to_string()).dispatch().await.into_json().await.unwrap();
+
+    let mut simulations = Vec::new();
+    for request in [
+        json!({"signedTransaction": signed["signedTransaction"]}),
+        // Simulated without signature
+        json!({"message": built["message"]}),
+    ] {
+        let response = client.post("/transactions/simulate").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+        simulations.push((response.status(), response.into_json::<Value>().await.unwrap()));
+    }
+    let invalid_status = client.post("/transactions/simulate").header(ContentType::JSON)
+        .body(json!({"signedTransaction": signed["signedTransaction"], "message": built["message"]}).to_string())
+        .dispatch().await.status();
+
+    client.terminate().await.shutdown().await;
+
+    let expected_simulation = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "6000", "decimals": 9, "uiAmountString": "0.000006"}, "contract": null}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9, "uiAmountString": "0.000001"}, "contract": null}],
+        "status": "Success",
+        "logs": ["Program 11111111111111111111111111111111 invoke [1]", "Program 11111111111111111111111111111111 success"],
+        "unitsConsumed": 150
+    });
+    for (status, simulation) in simulations {
+        assert_eq!(status, Status::Ok);
+        assert_json_eq!(simulation, expected_simulation);
+    }
+    assert_eq!(invalid_status, Status::BadRequest);
+}
+
+#[rocket::async_test]
+async fn test_simulate_transaction_error() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8035");
+
+    let rocket = common::setup_with_rpc_mocks(vec![
+        rpc_mock("getMultipleAccounts", json!({"context": {"slot": 234381065}, "value": [null, null, null]})),
+        rpc_mock("simulateTransaction", json!({
+            "context": {"slot": 234381065},
+            "value": {
+                "err": {"InstructionError": [0, {"Custom": 1}]},
+                "accounts": null,
+                "logs": [
+                    "Program 11111111111111111111111111111111 invoke [1]",
+                    "Transfer: insufficient lamports 0, need 1000",
+                    "Program 11111111111111111111111111111111 failed: custom program error: 0x1"
+                ],
+                "returnData": null,
+                "unitsConsumed": 150
+            }
+        })),
+    ]).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let transfer = json!({
+        "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": {"amount": "1000", "decimals": 9}, "contract": null}],
+        "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": {"amount": "1000", "decimals": 9}, "contract": null}]
+    });
+    let signed: Value = client.post("/transactions/sign").header(ContentType::JSON).body(transfer.to_string()).dispatch().await.into_json().await.unwrap();
+    let response = client.post("/transactions/simulate").header(ContentType::JSON).body(json!({"signedTransaction": signed["signedTransaction"]}).to_string()).dispatch().await;
+    let simulation_status = response.status();
+    let simulation: Value = response.into_json().await.unwrap();
+
+    let response = client.post("/fee/estimate").header(ContentType::JSON).body(transfer.to_string()).dispatch().await;
+    let estimate_status = response.status();
+    let estimate: Value = response.into_json().await.unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(simulation_status, Status::Ok);
+    assert_eq!(simulation["status"], "Failed");
+    assert_eq!(simulation["error"], "Instruction 0 (System Program) failed: account does not have enough SOL to perform the operation");
+    assert_eq!(simulation["from"], json!([]));
+    assert_eq!(simulation["to"], json!([]));
+    assert_eq!(simulation["logs"].as_array().unwrap().len(), 3);
+
+    // A transfer failing in simulation has no fee estimate
+    assert_eq!(estimate_status, Status::BadRequest);
+    assert!(estimate["code"].as_str().unwrap().starts_with("Transaction fails in simulation: Instruction 0"));
+}
+
+#[rocket::async_test]
+async fn test_send_transaction_wait_for_status() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8036");
+
+    let rocket = common::setup().await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let request = json!({"signedTransaction": SIGNED_TRANSACTION});
+    let response = client.post("/transactions/send?waitFor=confirmed").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+    let send_status = response.status();
+    let sent: Value = response.into_json().await.unwrap();
+
+    let response = client.get("/transactions/4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9/status").dispatch().await;
+    let status_status = response.status();
+    let status: Value = response.into_json().await.unwrap();
+
+    let invalid_status = client.get("/transactions/invalid/status").dispatch().await.status();
+
+    client.terminate().await.shutdown().await;
+
+    // The finalized status of the mock satisfies the confirmed commitment
+    let expected_status = json!({
+        "txnHash": "4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9",
+        "slot": 48,
+        "confirmations": null,
+        "confirmationStatus": "finalized",
+        "status": "Success"
+    });
+
+    assert_eq!(send_status, Status::Ok);
+    assert_json_eq!(sent, json!({
+        "txnHash": "4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9",
+        "status": expected_status
+    }));
+
+    assert_eq!(status_status, Status::Ok);
+    assert_json_eq!(status, expected_status);
+
+    assert_eq!(invalid_status, Status::BadRequest);
+}
+
+#[rocket::async_test]
+async fn test_transaction_status_not_found() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8037");
+
+    let rocket = common::setup_with_rpc_mocks(vec![
+        rpc_mock("getSignatureStatuses", json!({"context": {"slot": 82}, "value": [null]})),
+    ]).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response = client.get("/transactions/4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9/status").dispatch().await;
+    let status = response.status();
+    let body: Value = response.into_json().await.unwrap();
+
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(status, Status::NotFound);
+    assert_eq!(body["code"], "Transaction 4rCHUpQW8jJdXgiwcJRyHeoudwKbkZQBYp5hKK85MK39KjKM3MWVzD9fzd2gxF3hWuT3vjGq3kZ77jCbXdtrARi9 is not known by the cluster");
+}
+
+#[rocket::async_test]
+async fn test_interest_bearing_mint() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8038");
+
+    // Token-2022 mint with 6 decimals whose accounts accrued 5% of interest over a year, the current rate is 0
+    let mint_data = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAMqaOwAAAAAGAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQoANAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA9AGAhOEBAAAAAAAA";
+    let rocket = common::setup_with_rpc_mocks(vec![
+        rpc_mock("getAccountInfo", json!({
+            "context": {"slot": 234381065},
+            "value": {"data": [mint_data, "base64"], "executable": false, "lamports": 2477760, "owner": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb", "rentEpoch": 0, "space": 222}
+        })),
+        rpc_mock("getTokenAccountsByOwner", json!({
+            "context": {"slot": 234381065},
+            "value": [{
+                "account": {
+                    "data": {
+                        "program": "spl-token-2022",
+                        "parsed": {
+                            "info": {
+                                "isNative": false,
+                                "mint": "2gqEFSwt7v7ZemPstoSnQhXdz2VbN3rzaQbmHaPubpLs",
+                                "owner": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH",
+                                "state": "initialized",
+                                "tokenAmount": {"amount": "2500000", "decimals": 6, "uiAmount": 2.5, "uiAmountString": "2.5"}
+                            },
+                            "type": "account"
+                        },
+                        "space": 170
+                    },
+                    "executable": false,
+                    "lamports": 2074080,
+                    "owner": "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+                    "rentEpoch": 0,
+                    "space": 170
+                },
+                "pubkey": "CsLUt89gf9j2f5KE8CMRbfMmA5ET4QLoMiEKsv5PbFfZ"
+            }]
+        })),
+    ]).await;
+    let client = Client::tracked(rocket).await.unwrap();
+
+    let response = client.get("/address/22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH/balance?contract=2gqEFSwt7v7ZemPstoSnQhXdz2VbN3rzaQbmHaPubpLs").dispatch().await;
+    let balance_status = response.status();
+    let balance: Value = response.into_json().await.unwrap();
+
+    let response = client.get("/address/22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH/tokens").dispatch().await;
+    let tokens_status = response.status();
+    let tokens: Value = response.into_json().await.unwrap();
+
+    // The UI amount of the mint depends on the accrued interest, transfers only take the raw amount
+    let mut transfer_statuses = Vec::new();
+    for amount in [json!({"amount": "1000000", "decimals": 6}), json!({"uiAmountString": "1", "decimals": 6})] {
+        let request = json!({
+            "from": [{"adress": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN", "amount": amount, "contract": "2gqEFSwt7v7ZemPstoSnQhXdz2VbN3rzaQbmHaPubpLs"}],
+            "to": [{"adress": "22NCQiCUivo4kijCSJwyA7qhSrx4kpba5UXvzdCZ66hH", "amount": amount, "contract": "2gqEFSwt7v7ZemPstoSnQhXdz2VbN3rzaQbmHaPubpLs"}],
+            "keyId": "B45rg4rxKLncrNP8vYZLaT32qq1ohEDLvkNV8aQr8KzN"
+        });
+        let response = client.post("/transactions/sign").header(ContentType::JSON).body(request.to_string()).dispatch().await;
+        transfer_statuses.push(response.status());
+    }
+
+    client.terminate().await.shutdown().await;
+
+    // 2.5 tokens with 5% of continuously compounded interest, instead of the 2.5 returned by the node
+    let expected_amount = json!({"amount": "2500000", "decimals": 6, "uiAmountString": "2.6281777409400604"});
+
+    assert_eq!(balance_status, Status::Ok);
+    assert_json_eq!(balance["balance"], expected_amount);
+    assert_json_eq!(balance["accounts"][0]["amount"], expected_amount);
+
+    assert_eq!(tokens_status, Status::Ok);
+    let holdings = tokens["tokens"].as_array().unwrap();
+    // The mock returns the account for the filters of both token programs
+    let holding = holdings.iter().find(|holding| holding["programId"] == "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap();
+    assert_json_eq!(holding["balance"], expected_amount);
+
+    assert_eq!(transfer_statuses, vec![Status::Ok, Status::BadRequest]);
+}
+
+#[rocket::async_test]
+async fn test_hd_wallet_generated_mnemonic() {
+    dotenv::dotenv().ok();
+    env::set_var("ROCKET_PORT", "8039");
+
+    // The insecure mode returns the generated mnemonic to the client
+    let rocket = common::setup_with(|config| config.insecure_private_keys = true).await;
+    let client = Client::tracked(rocket).await.unwrap();
+    let response = client.post("/hd-wallets").dispatch().await;
+    let insecure_status = response.status();
+    let insecure_wallet: Value = response.into_json().await.unwrap();
+    client.terminate().await.shutdown().await;
+
+    // The keystore keeps the generated seed
+    let keystore_path = env::temp_dir().join(format!("blockchain-solana-hd-generated-{}", std::process::id()));
+    let path = keystore_path.clone();
+    let rocket = common::setup_with(|config| {
+        config.signer = SignerKind::Keystore;
+        config.keystore_path = path.to_string_lossy().to_string();
+        config.keystore_passphrase = Some("correct horse battery staple".to_string());
+        config.keystore_scrypt_log_n = 10;
+    }).await;
+    let client = Client::tracked(rocket).await.unwrap();
+    let response = client.post("/hd-wallets").dispatch().await;
+    let keystore_status = response.status();
+    let keystore_wallet: Value = response.into_json().await.unwrap();
+    client.terminate().await.shutdown().await;
+
+    assert_eq!(insecure_status, Status::Ok);
+    assert_eq!(insecure_wallet["mnemonic"].as_str().unwrap().split_whitespace().count(), 24);
+
+    assert_eq!(keystore_status, Status::Ok);
+    assert!(keystore_wallet.get("mnemonic").is_none());
+
+    std::fs::remove_dir_all(&keystore_path).unwrap();
 }
\ No newline at end of file